@@ -1,11 +1,70 @@
-use render::{render_list, Position, RenderList, Size};
+use std::collections::VecDeque;
+#[cfg(feature = "persist")]
+use std::path::Path;
+use std::sync::mpsc::Receiver;
+
+use clock::{Clock, SystemClock};
+use command::{Command, Executor};
+use dispatcher::{self, Dispatcher};
+use history::History;
+use measure::{TerminalMeasurer, TextMeasurer};
+use middleware::{self, Middleware};
+use record::ActionLog;
+use render::{render_list_with, Position, RenderList, Size, DEFAULT_VIEWPORT};
+use state::{LocalAction, LocalStates, RenderContext, StateKey};
+use subscription::{Subscription, Subscriptions};
 use vnode::{DynamicNode, StaticNode};
 
+#[cfg(feature = "persist")]
+use persist::{self, PersistError, Version};
+#[cfg(feature = "persist")]
+use serde::de::DeserializeOwned;
+#[cfg(feature = "persist")]
+use serde::Serialize;
+#[cfg(feature = "persist")]
+use serde_json::Value;
+
+type ReducerFn<S, A> = dyn Fn(S, A) -> (S, Vec<Command<A>>);
+
+/// An `App` stays on the thread that created it, since templates,
+/// handlers and middleware are not required to be `Send`. Other threads
+/// feed it actions through a `Dispatcher`.
+///
+/// ```compile_fail
+/// fn send<T: Send>() {}
+/// send::<cinnabar::App<u32, (), u32>>();
+/// ```
 pub struct App<S, M, A> {
-    store: S,
+    // Only `None` while an action is being reduced.
+    store: Option<S>,
+    queue: VecDeque<A>,
+    dispatcher: Dispatcher<A>,
+    pending: Receiver<A>,
     vnode: DynamicNode<S, M, A>,
-    reducer: Box<Fn(S, A) -> S + 'static>,
+    reducer: Box<ReducerFn<S, A>>,
+    middleware: Vec<Box<dyn Middleware<S, A>>>,
+    history: Option<History<S, A>>,
+    executor: Option<Box<dyn Executor<A>>>,
+    subscriptions: Option<Subscriptions<S, A>>,
+    clock: Box<dyn Clock>,
+    states: LocalStates,
+    viewport: Size,
+    // Terminal cells unless one was set.
+    measurer: Option<Box<dyn TextMeasurer>>,
     last_render: StaticNode<A>,
+    // Render list of `last_render` in `viewport`, until either changes.
+    layout: Option<RenderList<A>>,
+}
+
+fn render<S, M, A>(
+    vnode: &DynamicNode<S, M, A>,
+    store: &S,
+    states: &mut LocalStates,
+    viewport: Size,
+) -> StaticNode<A> {
+    let node = vnode.render_with(store, &mut RenderContext::new(states, viewport));
+    states.collect();
+    node
 }
 
 impl<S, M, A> App<S, M, A> {
@@ -16,27 +75,442 @@ impl<S, M, A> App<S, M, A> {
     ) -> App<S, M, A> {
         let dynamic_node = vnode.into();
 
-        let last_render = dynamic_node.render(&store);
+        let mut states = LocalStates::new();
+        let last_render = render(&dynamic_node, &store, &mut states, DEFAULT_VIEWPORT);
+        let (dispatcher, pending) = dispatcher::dispatcher();
+
+        App {
+            store: Some(store),
+            queue: VecDeque::new(),
+            dispatcher,
+            pending,
+            vnode: dynamic_node,
+            reducer: Box::new(move |store, action| (reducer(store, action), Vec::new())),
+            middleware: Vec::new(),
+            history: None,
+            executor: None,
+            subscriptions: None,
+            clock: Box::new(SystemClock),
+            states,
+            viewport: DEFAULT_VIEWPORT,
+            measurer: None,
+            last_render,
+            layout: None,
+        }
+    }
+
+    /// Like `new`, but the reducer can return commands for side effects.
+    /// They are handed to `executor`, and the actions they produce are
+    /// dispatched by `poll`, or by `flush` if they finish while it runs.
+    pub fn with_commands<T: Into<DynamicNode<S, M, A>>>(
+        store: S,
+        vnode: T,
+        reducer: impl Fn(S, A) -> (S, Vec<Command<A>>) + 'static,
+        executor: impl Executor<A> + 'static,
+    ) -> App<S, M, A> {
+        let dynamic_node = vnode.into();
+
+        let mut states = LocalStates::new();
+        let last_render = render(&dynamic_node, &store, &mut states, DEFAULT_VIEWPORT);
+        let (dispatcher, pending) = dispatcher::dispatcher();
 
         App {
-            store,
+            store: Some(store),
+            queue: VecDeque::new(),
+            dispatcher,
+            pending,
             vnode: dynamic_node,
             reducer: Box::new(reducer),
+            middleware: Vec::new(),
+            history: None,
+            executor: Some(Box::new(executor)),
+            subscriptions: None,
+            clock: Box::new(SystemClock),
+            states,
+            viewport: DEFAULT_VIEWPORT,
+            measurer: None,
             last_render,
+            layout: None,
         }
     }
 
+    /// Like `new`, but the store is read from a snapshot written by `save`.
+    /// See `persist::load` for how `migrate` upgrades older snapshots.
+    #[cfg(feature = "persist")]
+    pub fn restore<T, P, F>(
+        path: P,
+        version: Version,
+        migrate: F,
+        vnode: T,
+        reducer: impl Fn(S, A) -> S + 'static,
+    ) -> Result<App<S, M, A>, PersistError>
+    where
+        S: DeserializeOwned,
+        T: Into<DynamicNode<S, M, A>>,
+        P: AsRef<Path>,
+        F: Fn(Version, Value) -> Result<Value, PersistError>,
+    {
+        let store = persist::load(path, version, migrate)?;
+        Ok(App::new(store, vnode, reducer))
+    }
+
+    /// Like `restore`, but with a reducer that returns commands, as in
+    /// `with_commands`.
+    #[cfg(feature = "persist")]
+    pub fn restore_with_commands<T, P, F>(
+        path: P,
+        version: Version,
+        migrate: F,
+        vnode: T,
+        reducer: impl Fn(S, A) -> (S, Vec<Command<A>>) + 'static,
+        executor: impl Executor<A> + 'static,
+    ) -> Result<App<S, M, A>, PersistError>
+    where
+        S: DeserializeOwned,
+        T: Into<DynamicNode<S, M, A>>,
+        P: AsRef<Path>,
+        F: Fn(Version, Value) -> Result<Value, PersistError>,
+    {
+        let store = persist::load(path, version, migrate)?;
+        Ok(App::with_commands(store, vnode, reducer, executor))
+    }
+
+    /// Writes a snapshot of the store to `path`, tagged with `version`.
+    #[cfg(feature = "persist")]
+    pub fn save<P: AsRef<Path>>(&self, path: P, version: Version) -> Result<(), PersistError>
+    where
+        S: Serialize,
+    {
+        persist::save(path, version, self.store())
+    }
+
+    /// Adds `middleware` around the reducer. The first one added sees
+    /// actions first.
+    pub fn middleware(mut self, middleware: impl Middleware<S, A> + 'static) -> Self {
+        self.middleware.push(Box::new(middleware));
+        self
+    }
+
+    /// Listens to the sources `subscribe` returns for the current store.
+    /// It is called again after every action, starting sources whose key is
+    /// new and stopping those that are gone.
+    pub fn subscriptions(
+        mut self,
+        subscribe: impl Fn(&S) -> Vec<Subscription<A>> + 'static,
+    ) -> Self {
+        self.subscriptions = Some(Subscriptions::new(subscribe));
+        self.update_subscriptions();
+        self
+    }
+
+    /// Replaces the clock subscriptions are timed by. Running subscriptions
+    /// are restarted against the new clock.
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Box::new(clock);
+        if let Some(ref mut subscriptions) = self.subscriptions {
+            subscriptions.restart();
+        }
+        self.update_subscriptions();
+        self
+    }
+
+    /// Replaces how text is measured during layout. Defaults to terminal
+    /// cells.
+    pub fn measurer(mut self, measurer: impl TextMeasurer + 'static) -> Self {
+        self.measurer = Some(Box::new(measurer));
+        self.layout = None;
+        self
+    }
+
+    /// Uses `measurer` unless one was set with `measurer`.
+    pub(crate) fn default_measurer(&mut self, measurer: Box<dyn TextMeasurer>) {
+        if self.measurer.is_none() {
+            self.measurer = Some(measurer);
+            self.layout = None;
+        }
+    }
+
+    fn text_measurer(&self) -> &dyn TextMeasurer {
+        match self.measurer {
+            Some(ref measurer) => &**measurer,
+            None => &TerminalMeasurer,
+        }
+    }
+
+    /// Starts recording every action and the store it produces, which makes
+    /// `undo`, `redo` and `jump` available.
+    pub fn enable_history(mut self) -> Self
+    where
+        S: Clone,
+        A: Clone,
+    {
+        self.history = Some(History::new(self.store(), None));
+        self
+    }
+
+    /// Like `enable_history`, but only keeps the last `capacity` entries.
+    pub fn enable_history_with_capacity(mut self, capacity: usize) -> Self
+    where
+        S: Clone,
+        A: Clone,
+    {
+        self.history = Some(History::new(self.store(), Some(capacity)));
+        self
+    }
+
+    pub fn history(&self) -> Option<&History<S, A>> {
+        self.history.as_ref()
+    }
+
+    /// Restores the store from history entry `index` and renders it.
+    /// Commands are not run again. Does nothing without history.
+    pub fn jump(mut self, index: usize) -> Self {
+        let store = match self.history {
+            Some(ref mut history) => history.jump(index),
+            None => None,
+        };
+
+        if let Some(store) = store {
+            self.store = Some(store);
+            self.update_subscriptions();
+            self.rerender();
+        }
+
+        self
+    }
+
+    pub fn undo(self) -> Self {
+        match self.history.as_ref().map(|history| history.cursor()) {
+            Some(cursor) if cursor > 0 => self.jump(cursor - 1),
+            _ => self,
+        }
+    }
+
+    pub fn redo(self) -> Self {
+        match self.history.as_ref().map(|history| history.cursor()) {
+            Some(cursor) => self.jump(cursor + 1),
+            None => self,
+        }
+    }
+
+    pub fn store(&self) -> &S {
+        self.store.as_ref().unwrap()
+    }
+
+    fn update_subscriptions(&mut self) {
+        if let (Some(subscriptions), Some(store)) =
+            (self.subscriptions.as_mut(), self.store.as_ref())
+        {
+            subscriptions.update(store, self.clock.now());
+        }
+    }
+
+    fn rerender(&mut self) {
+        let store = self.store.as_ref().unwrap();
+        let node = render(&self.vnode, store, &mut self.states, self.viewport);
+
+        if !node.ptr_eq(&self.last_render) {
+            self.layout = None;
+        }
+        self.last_render = node;
+    }
+
+    pub fn viewport(&self) -> Size {
+        self.viewport
+    }
+
+    /// Changes the size the view is shown in. Templates are rendered again,
+    /// since they may depend on it.
+    pub fn resize(&mut self, size: Size) {
+        if size != self.viewport {
+            self.viewport = size;
+            self.layout = None;
+            self.rerender();
+        }
+    }
+
+    /// The render list of the current view filling the viewport. It is only
+    /// laid out again after the view or the viewport changed.
+    pub fn layout(&mut self) -> &RenderList<A> {
+        if self.layout.is_none() {
+            self.layout = Some(render_list_with(
+                &self.last_render,
+                (0, 0),
+                self.viewport,
+                self.text_measurer(),
+            ));
+        }
+        self.layout.as_ref().unwrap()
+    }
+
     pub fn view(&self) -> &StaticNode<A> {
         &self.last_render
     }
 
     pub fn render_list(&self, position: Position, size: Size) -> RenderList<A> {
-        render_list(&self.last_render, position, size)
+        render_list_with(&self.last_render, position, size, self.text_measurer())
     }
 
     pub fn action(mut self, action: A) -> Self {
-        self.store = (self.reducer)(self.store, action);
-        self.last_render = self.vnode.render(&self.store);
+        self.dispatch(action);
+        self
+    }
+
+    /// Dispatches `action` along with everything queued before it, then
+    /// renders once.
+    pub fn dispatch(&mut self, action: A) {
+        self.queue.push_back(action);
+        self.flush();
+    }
+
+    /// Like `dispatch`, but for several actions with a single render.
+    pub fn dispatch_all<I: IntoIterator<Item = A>>(&mut self, actions: I) {
+        self.queue.extend(actions);
+        self.flush();
+    }
+
+    /// Queues `action` to be dispatched by the next `flush`.
+    pub fn enqueue(&mut self, action: A) {
+        self.queue.push_back(action);
+    }
+
+    /// Reduces queued actions in order until the queue is empty. Actions of
+    /// commands that finish meanwhile are queued behind them. Renders once
+    /// at the end, if anything was dispatched.
+    pub fn flush(&mut self) {
+        if self.queue.is_empty() {
+            return;
+        }
+
+        while let Some(action) = self.queue.pop_front() {
+            self.reduce(action);
+
+            if let Some(ref mut executor) = self.executor {
+                self.queue.extend(executor.poll());
+            }
+        }
+
+        self.update_subscriptions();
+        self.rerender();
+    }
+
+    /// A handle other threads can send actions through.
+    pub fn dispatcher(&self) -> Dispatcher<A> {
+        self.dispatcher.clone()
+    }
+
+    /// Dispatches the actions sent through dispatchers since the last call,
+    /// rendering once. Returns how many there were.
+    pub fn run_pending(&mut self) -> usize {
+        let actions: Vec<A> = self.pending.try_iter().collect();
+        let count = actions.len();
+
+        self.dispatch_all(actions);
+        count
+    }
+
+    fn reduce(&mut self, action: A) {
+        let recorded = self
+            .history
+            .as_ref()
+            .map(|history| history.clone_action(&action));
+
+        let reducer = &self.reducer;
+        let mut commands = Vec::new();
+        // Stays false if a middleware rejected the action.
+        let mut reduced = false;
+
+        let store = middleware::run(
+            &mut self.middleware,
+            self.store.take().unwrap(),
+            action,
+            &mut |store, action| {
+                let (store, new_commands) = reducer(store, action);
+                commands.extend(new_commands);
+                reduced = true;
+                store
+            },
+        );
+
+        if let (Some(history), Some(action)) = (self.history.as_mut(), recorded) {
+            if reduced {
+                history.push(action, &store);
+            }
+        }
+        self.store = Some(store);
+
+        if let Some(ref mut executor) = self.executor {
+            for command in commands {
+                executor.execute(command);
+            }
+        }
+    }
+
+    /// Dispatches every action of `log` in order, as fast as possible.
+    ///
+    /// The log already holds the actions that commands and subscriptions
+    /// produced while recording, so commands returned meanwhile are dropped
+    /// and subscriptions are paused, to not dispatch those twice.
+    pub fn replay(mut self, log: ActionLog<A>) -> Self {
+        let executor = self.executor.take();
+        let subscriptions = self.subscriptions.take();
+
+        self.dispatch_all(log.into_actions());
+
+        self.executor = executor;
+        self.subscriptions = subscriptions;
+        self.update_subscriptions();
+        self
+    }
+
+    /// Dispatches the actions of commands that finished and of subscriptions
+    /// that fired since the last poll.
+    pub fn poll(mut self) -> Self {
+        let actions = self.effects();
+        self.dispatch_all(actions);
+        self
+    }
+
+    /// Like `poll`, but also dispatches the actions sent through dispatchers
+    /// and anything queued. Renders once. Returns how many actions there were.
+    pub fn tick(&mut self) -> usize {
+        let mut actions: Vec<A> = self.pending.try_iter().collect();
+        actions.extend(self.effects());
+
+        let count = self.queue.len() + actions.len();
+        self.dispatch_all(actions);
+        count
+    }
+
+    fn effects(&mut self) -> Vec<A> {
+        let mut actions = match self.executor {
+            Some(ref mut executor) => executor.poll(),
+            None => Vec::new(),
+        };
+
+        if let Some(ref mut subscriptions) = self.subscriptions {
+            actions.extend(subscriptions.poll(self.clock.now()));
+        }
+
+        actions
+    }
+
+    /// Updates the local state of the template identified by `key` without
+    /// going through the reducer.
+    pub fn local_action<K: Into<StateKey>, LA: 'static>(mut self, key: K, action: LA) -> Self {
+        self.dispatch_local(LocalAction::new(key, action));
+        self
+    }
+
+    /// Like `local_action`, for actions created by a `LocalHandle`.
+    pub fn dispatch_local(&mut self, action: LocalAction) {
+        if self.states.update(action) {
+            self.rerender();
+        }
+    }
+
+    pub fn refresh(mut self) -> Self {
+        self.rerender();
         self
     }
 }
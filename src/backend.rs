@@ -0,0 +1,136 @@
+use std::collections::VecDeque;
+use std::io;
+use std::time::Duration;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use measure::{grapheme_width, TerminalMeasurer, TextMeasurer};
+use render::{Position, RenderCommand, RenderList, Size};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    Key(char),
+    Click(Position),
+    Resize(Size),
+    Quit,
+}
+
+/// Where an `App` gets its input from and draws to, e.g. a terminal.
+pub trait Backend {
+    fn size(&mut self) -> io::Result<Size>;
+
+    /// Returns the next input event, waiting at most `timeout` for one.
+    fn poll(&mut self, timeout: Duration) -> io::Result<Option<Event>>;
+
+    /// Replaces what is on screen with `list`.
+    fn present<A>(&mut self, list: &RenderList<A>) -> io::Result<()>;
+
+    /// How text is measured for this backend. Defaults to terminal cells.
+    fn measurer(&self) -> Box<dyn TextMeasurer> {
+        Box::new(TerminalMeasurer)
+    }
+}
+
+/// A backend that plays back a script of events and draws into a grid of
+/// characters, for tests. It never waits and sends `Quit` once the script
+/// is over.
+pub struct FakeBackend {
+    size: Size,
+    script: VecDeque<Option<Event>>,
+    screen: Vec<String>,
+    frames: usize,
+}
+
+impl FakeBackend {
+    pub fn new(size: Size) -> Self {
+        FakeBackend {
+            size,
+            script: VecDeque::new(),
+            screen: Vec::new(),
+            frames: 0,
+        }
+    }
+
+    pub fn event(mut self, event: Event) -> Self {
+        self.script.push_back(Some(event));
+        self
+    }
+
+    /// Ends the current frame without sending anything.
+    pub fn idle(mut self) -> Self {
+        self.script.push_back(None);
+        self
+    }
+
+    /// Lines of the last presented frame, without trailing spaces.
+    pub fn screen(&self) -> &[String] {
+        &self.screen
+    }
+
+    /// How many times something was presented.
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+}
+
+impl Backend for FakeBackend {
+    fn size(&mut self) -> io::Result<Size> {
+        Ok(self.size)
+    }
+
+    fn poll(&mut self, _timeout: Duration) -> io::Result<Option<Event>> {
+        match self.script.pop_front() {
+            Some(Some(Event::Resize(size))) => {
+                self.size = size;
+                Ok(Some(Event::Resize(size)))
+            }
+            Some(event) => Ok(event),
+            None => Ok(Some(Event::Quit)),
+        }
+    }
+
+    fn present<A>(&mut self, list: &RenderList<A>) -> io::Result<()> {
+        let (width, height) = (self.size.0 as usize, self.size.1 as usize);
+
+        // Every cell holds one grapheme cluster. The cell after a wide one
+        // is left empty, as a terminal would cover it.
+        let mut grid = vec![vec![" ".to_string(); width]; height];
+
+        for command in list {
+            match command {
+                RenderCommand::Text(text) => {
+                    let (mut x, y) = (text.position.0 as usize, text.position.1 as usize);
+                    if y >= height {
+                        continue;
+                    }
+
+                    for grapheme in text.content.graphemes(true) {
+                        let cells = grapheme_width(grapheme) as usize;
+                        if cells == 0 {
+                            continue;
+                        }
+                        // The rest of the run is off screen, even if the
+                        // next grapheme is narrow enough to fit.
+                        if x + cells > width {
+                            break;
+                        }
+
+                        grid[y][x] = grapheme.to_string();
+                        for cell in &mut grid[y][x + 1..x + cells] {
+                            cell.clear();
+                        }
+                        x += cells;
+                    }
+                }
+            }
+        }
+
+        self.screen = grid
+            .into_iter()
+            .map(|line| line.concat().trim_end().to_string())
+            .collect();
+        self.frames += 1;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,142 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+pub type Task<A> = Box<dyn FnOnce() -> A + Send>;
+
+/// Work returned by a reducer that runs outside of it. The action the task
+/// returns is dispatched back into the `App` once it finishes.
+pub struct Command<A> {
+    delay: Option<Duration>,
+    task: Task<A>,
+}
+
+impl<A> Command<A> {
+    pub fn new(task: impl FnOnce() -> A + Send + 'static) -> Self {
+        Command {
+            delay: None,
+            task: Box::new(task),
+        }
+    }
+
+    /// Runs `task` once `delay` has passed.
+    pub fn delay(delay: Duration, task: impl FnOnce() -> A + Send + 'static) -> Self {
+        Command {
+            delay: Some(delay),
+            task: Box::new(task),
+        }
+    }
+
+    pub fn delay_duration(&self) -> Option<Duration> {
+        self.delay
+    }
+
+    /// Runs the task right away, ignoring any delay.
+    pub fn run(self) -> A {
+        (self.task)()
+    }
+}
+
+pub trait Executor<A> {
+    fn execute(&mut self, command: Command<A>);
+
+    /// Actions of commands that finished since the last poll.
+    fn poll(&mut self) -> Vec<A>;
+}
+
+/// Runs every command on its own thread.
+pub struct ThreadExecutor<A> {
+    sender: Sender<A>,
+    receiver: Receiver<A>,
+}
+
+impl<A: Send + 'static> Default for ThreadExecutor<A> {
+    fn default() -> ThreadExecutor<A> {
+        ThreadExecutor::new()
+    }
+}
+
+impl<A: Send + 'static> ThreadExecutor<A> {
+    pub fn new() -> Self {
+        let (sender, receiver) = channel();
+        ThreadExecutor { sender, receiver }
+    }
+}
+
+impl<A: Send + 'static> Executor<A> for ThreadExecutor<A> {
+    fn execute(&mut self, command: Command<A>) {
+        let sender = self.sender.clone();
+
+        thread::spawn(move || {
+            if let Some(delay) = command.delay {
+                thread::sleep(delay);
+            }
+            // The App may be gone already, in which case nobody is interested.
+            let _ = sender.send(command.run());
+        });
+    }
+
+    fn poll(&mut self) -> Vec<A> {
+        self.receiver.try_iter().collect()
+    }
+}
+
+/// Queues commands and runs them synchronously when told to. Clones share
+/// the same queue, so a test can keep one to drive the App's copy.
+pub struct FakeExecutor<A> {
+    queue: Rc<RefCell<Vec<Command<A>>>>,
+    finished: Rc<RefCell<Vec<A>>>,
+}
+
+impl<A> Clone for FakeExecutor<A> {
+    fn clone(&self) -> Self {
+        FakeExecutor {
+            queue: self.queue.clone(),
+            finished: self.finished.clone(),
+        }
+    }
+}
+
+impl<A> Default for FakeExecutor<A> {
+    fn default() -> FakeExecutor<A> {
+        FakeExecutor::new()
+    }
+}
+
+impl<A> FakeExecutor<A> {
+    pub fn new() -> Self {
+        FakeExecutor {
+            queue: Rc::new(RefCell::new(Vec::new())),
+            finished: Rc::new(RefCell::new(Vec::new())),
+        }
+    }
+
+    pub fn pending(&self) -> usize {
+        self.queue.borrow().len()
+    }
+
+    /// Runs all queued commands, ignoring their delays. Returns how many ran.
+    pub fn run_all(&self) -> usize {
+        let commands: Vec<Command<A>> = self.queue.borrow_mut().drain(..).collect();
+        let count = commands.len();
+
+        for command in commands {
+            let action = command.run();
+            self.finished.borrow_mut().push(action);
+        }
+
+        count
+    }
+}
+
+impl<A> Executor<A> for FakeExecutor<A> {
+    fn execute(&mut self, command: Command<A>) {
+        self.queue.borrow_mut().push(command);
+    }
+
+    fn poll(&mut self) -> Vec<A> {
+        self.finished.borrow_mut().drain(..).collect()
+    }
+}
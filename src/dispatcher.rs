@@ -0,0 +1,27 @@
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+/// Sends actions to an `App` from any thread. They are dispatched on the
+/// `App`'s thread by `App::run_pending`.
+pub struct Dispatcher<A> {
+    sender: Sender<A>,
+}
+
+impl<A> Clone for Dispatcher<A> {
+    fn clone(&self) -> Self {
+        Dispatcher {
+            sender: self.sender.clone(),
+        }
+    }
+}
+
+impl<A> Dispatcher<A> {
+    /// Returns `false` if the `App` is gone and the action was dropped.
+    pub fn dispatch(&self, action: A) -> bool {
+        self.sender.send(action).is_ok()
+    }
+}
+
+pub(crate) fn dispatcher<A>() -> (Dispatcher<A>, Receiver<A>) {
+    let (sender, receiver) = channel();
+    (Dispatcher { sender }, receiver)
+}
@@ -1,19 +1,23 @@
 #[macro_export]
 macro_rules! elements_for {
     ($s:ident, $m:ident, $a:ident) => {
-        use cinnabar::vnode::builder::Builder;
+        use cinnabar::vnode::builder::{ContainerBuilder, TextBuilder};
         use cinnabar::vnode::TextContent;
 
-        pub fn panel() -> Builder<$s, $m, $a> {
-            Builder::<$s, $m, $a>::container("panel")
+        pub fn panel() -> ContainerBuilder<$s, $m, $a> {
+            ContainerBuilder::<$s, $m, $a>::new("panel")
         }
 
-        pub fn button() -> Builder<$s, $m, $a> {
-            Builder::<$s, $m, $a>::container("button")
+        pub fn button() -> ContainerBuilder<$s, $m, $a> {
+            ContainerBuilder::<$s, $m, $a>::new("button")
         }
 
-        pub fn text<T: Into<TextContent>>(content: T) -> Builder<$s, $m, $a> {
-            Builder::<$s, $m, $a>::text(content.into())
+        pub fn text<T: Into<TextContent>>(content: T) -> TextBuilder<$s, $m, $a> {
+            TextBuilder::<$s, $m, $a>::new(content.into())
+        }
+
+        pub fn rich(spans: Vec<cinnabar::vnode::Span<$a>>) -> TextBuilder<$s, $m, $a> {
+            TextBuilder::<$s, $m, $a>::rich(spans)
         }
     };
 }
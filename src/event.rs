@@ -1,10 +1,22 @@
 use std::sync::Arc;
 
+use state::LocalAction;
+
 pub struct ClickEvent {}
 
-#[derive(Clone)]
 pub struct Handlers<A> {
-    pub click: Option<Arc<Fn(ClickEvent) -> A>>,
+    pub click: Option<Arc<dyn Fn(ClickEvent) -> A>>,
+    /// Updates the local state of a template instead of dispatching.
+    pub local_click: Option<Arc<dyn Fn(ClickEvent) -> LocalAction>>,
+}
+
+impl<A> Clone for Handlers<A> {
+    fn clone(&self) -> Handlers<A> {
+        Handlers {
+            click: self.click.clone(),
+            local_click: self.local_click.clone(),
+        }
+    }
 }
 
 impl<A> Default for Handlers<A> {
@@ -15,7 +27,25 @@ impl<A> Default for Handlers<A> {
 
 impl<A> Handlers<A> {
     pub fn new() -> Self {
-        Handlers { click: None }
+        Handlers {
+            click: None,
+            local_click: None,
+        }
+    }
+
+    pub fn map<B>(&self, f: &Arc<dyn Fn(A) -> B>) -> Handlers<B>
+    where
+        A: 'static,
+        B: 'static,
+    {
+        Handlers {
+            click: self.click.as_ref().map(|click| {
+                let click = click.clone();
+                let f = f.clone();
+                Arc::new(move |event| f(click(event))) as Arc<dyn Fn(ClickEvent) -> B>
+            }),
+            local_click: self.local_click.clone(),
+        }
     }
 
     pub fn click<H>(&mut self, handler: H)
@@ -24,6 +54,18 @@ impl<A> Handlers<A> {
     {
         self.click = Some(Arc::new(handler));
     }
+
+    pub fn local_click<H>(&mut self, handler: H)
+    where
+        H: Fn(ClickEvent) -> LocalAction + 'static,
+    {
+        self.local_click = Some(Arc::new(handler));
+    }
+
+    /// Whether clicking does anything.
+    pub fn clickable(&self) -> bool {
+        self.click.is_some() || self.local_click.is_some()
+    }
 }
 
 // pub trait ClickHandler<A>: Fn(ClickEvent) -> A {}
@@ -0,0 +1,87 @@
+/// Every action dispatched to an `App` together with the store it produced,
+/// so the App can move back and forth between them.
+pub struct History<S, A> {
+    // The first entry holds the store history started from.
+    entries: Vec<(Option<A>, S)>,
+    cursor: usize,
+    capacity: Option<usize>,
+    clone_store: fn(&S) -> S,
+    clone_action: fn(&A) -> A,
+}
+
+impl<S: Clone, A: Clone> History<S, A> {
+    pub(crate) fn new(store: &S, capacity: Option<usize>) -> Self {
+        History {
+            entries: vec![(None, store.clone())],
+            cursor: 0,
+            capacity: capacity.map(|capacity| capacity.max(1)),
+            clone_store: S::clone,
+            clone_action: A::clone,
+        }
+    }
+}
+
+impl<S, A> History<S, A> {
+    pub(crate) fn clone_action(&self, action: &A) -> A {
+        (self.clone_action)(action)
+    }
+
+    /// Records `store` as the result of `action`, dropping anything that was
+    /// undone before, and the oldest entries beyond the capacity.
+    pub(crate) fn push(&mut self, action: A, store: &S) {
+        self.entries.truncate(self.cursor + 1);
+        self.entries.push((Some(action), (self.clone_store)(store)));
+        self.cursor += 1;
+
+        if let Some(capacity) = self.capacity {
+            let excess = self.entries.len().saturating_sub(capacity);
+            self.entries.drain(..excess);
+            self.cursor -= excess;
+        }
+    }
+
+    pub(crate) fn jump(&mut self, index: usize) -> Option<S> {
+        let store = self.entries.get(index).map(|(_, store)| store)?;
+        self.cursor = index;
+        Some((self.clone_store)(store))
+    }
+
+    /// The most entries kept, if limited.
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Index of the entry the App's store currently comes from.
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.cursor > 0
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.cursor + 1 < self.entries.len()
+    }
+
+    /// The action that led to each entry, `None` for the first one unless
+    /// it was dropped for capacity.
+    pub fn actions(&self) -> Vec<Option<&A>> {
+        self.entries
+            .iter()
+            .map(|(action, _)| action.as_ref())
+            .collect()
+    }
+
+    pub fn store(&self, index: usize) -> Option<&S> {
+        self.entries.get(index).map(|(_, store)| store)
+    }
+}
@@ -1,8 +1,10 @@
+use render;
 use vnode::IdValue;
 
 //
 // # Common
 //
+#[derive(Clone, PartialEq)]
 pub enum Position {
     Auto,
     Top(IdValue),
@@ -12,6 +14,7 @@ pub enum Position {
     Constant(usize, usize),
 }
 
+#[derive(Clone, PartialEq)]
 pub enum Size {
     Fill,
     FillRatio(usize),
@@ -21,6 +24,7 @@ pub enum Size {
     ConstantHeight(usize),
 }
 
+#[derive(Clone, PartialEq)]
 pub enum Align {
     None,
     Center,
@@ -30,6 +34,7 @@ pub enum Align {
     Bottom,
 }
 
+#[derive(Clone, PartialEq)]
 pub enum Offset {
     None,
     Up(usize),
@@ -38,14 +43,34 @@ pub enum Offset {
     Right(usize),
 }
 
+//
+// # Text
+//
+
+/// How a line of text that does not fit its width is cut. Only lines of
+/// text that does not wrap can be too wide.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Overflow {
+    #[default]
+    Clip,
+    /// Ends the line with an ellipsis.
+    Ellipsis,
+    /// Replaces the middle of the line with an ellipsis, keeping both ends.
+    MiddleEllipsis,
+    /// Skips the start of the line, e.g. to scroll it like a marquee.
+    Scroll(u16),
+}
+
 //
 // # Container
 //
+#[derive(Clone, Debug, PartialEq)]
 pub enum Direction {
     Row,
     Column,
 }
 
+#[derive(Clone, PartialEq)]
 pub struct ContainerLayout {
     direction: Direction,
     padding: (usize, usize, usize, usize),
@@ -62,14 +87,54 @@ impl Default for ContainerLayout {
     }
 }
 
+impl ContainerLayout {
+    pub(crate) fn set_direction(&mut self, direction: Direction) {
+        self.direction = direction;
+    }
+
+    pub(crate) fn direction(&self) -> &Direction {
+        &self.direction
+    }
+
+    /// Top, right, bottom and left padding.
+    pub(crate) fn padding(&self) -> (usize, usize, usize, usize) {
+        self.padding
+    }
+
+    /// Horizontal and vertical spacing between children.
+    pub(crate) fn spacing(&self) -> (usize, usize) {
+        self.spacing
+    }
+
+    /// This layout with the overrides of `item` that apply in `viewport`.
+    pub(crate) fn resolve(&self, item: &ItemLayout, viewport: render::Size) -> ContainerLayout {
+        let mut layout = self.clone();
+
+        for change in item.overrides(viewport) {
+            match change {
+                Override::Direction(direction) => layout.direction = direction.clone(),
+                Override::Padding(top, right, bottom, left) => {
+                    layout.padding = (*top, *right, *bottom, *left)
+                }
+                Override::Spacing(x, y) => layout.spacing = (*x, *y),
+                Override::Hidden => {}
+            }
+        }
+
+        layout
+    }
+}
+
 //
 // # Item
 //
+#[derive(Clone, PartialEq)]
 pub struct ItemLayout {
     position: Position,
     align: Align,
     offset: Offset,
     size: Size,
+    rules: Vec<(Media, Override)>,
 }
 
 impl Default for ItemLayout {
@@ -79,6 +144,87 @@ impl Default for ItemLayout {
             align: Align::None,
             offset: Offset::None,
             size: Size::Fill,
+            rules: Vec::new(),
         }
     }
 }
+
+impl ItemLayout {
+    pub(crate) fn add_rule(&mut self, media: Media, change: Override) {
+        self.rules.push((media, change));
+    }
+
+    /// Overrides whose media matches `viewport`, in the order they were added.
+    pub(crate) fn overrides(&self, viewport: render::Size) -> impl Iterator<Item = &Override> {
+        self.rules
+            .iter()
+            .filter(move |(media, _)| media.matches(viewport))
+            .map(|(_, change)| change)
+    }
+
+    pub(crate) fn hidden(&self, viewport: render::Size) -> bool {
+        self.overrides(viewport)
+            .any(|change| *change == Override::Hidden)
+    }
+}
+
+//
+// # Responsive rules
+//
+// Like media queries: an `Override` only applies while the viewport is
+// within the ranges of its `Media`. Later overrides win over earlier ones.
+//
+
+/// A range of viewport sizes. Bounds are inclusive, and a `Media` without
+/// any matches every viewport.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Media {
+    min_width: Option<u16>,
+    max_width: Option<u16>,
+    min_height: Option<u16>,
+    max_height: Option<u16>,
+}
+
+impl Media {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn min_width(mut self, width: u16) -> Self {
+        self.min_width = Some(width);
+        self
+    }
+
+    pub fn max_width(mut self, width: u16) -> Self {
+        self.max_width = Some(width);
+        self
+    }
+
+    pub fn min_height(mut self, height: u16) -> Self {
+        self.min_height = Some(height);
+        self
+    }
+
+    pub fn max_height(mut self, height: u16) -> Self {
+        self.max_height = Some(height);
+        self
+    }
+
+    pub fn matches(&self, (width, height): render::Size) -> bool {
+        self.min_width.is_none_or(|min| width >= min)
+            && self.max_width.is_none_or(|max| width <= max)
+            && self.min_height.is_none_or(|min| height >= min)
+            && self.max_height.is_none_or(|max| height <= max)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Override {
+    Direction(Direction),
+    /// Top, right, bottom and left padding of a container.
+    Padding(usize, usize, usize, usize),
+    /// Horizontal and vertical spacing between children of a container.
+    Spacing(usize, usize),
+    /// Leaves the item and everything in it out.
+    Hidden,
+}
@@ -1,19 +1,56 @@
 #[cfg(test)]
 #[macro_use]
 extern crate pretty_assertions;
+#[cfg(feature = "markdown")]
+extern crate pulldown_cmark;
+#[cfg(feature = "persist")]
+extern crate serde;
+#[cfg(feature = "persist")]
+#[macro_use]
+extern crate serde_json;
+extern crate unicode_linebreak;
+extern crate unicode_segmentation;
+extern crate unicode_width;
 
 mod app;
 mod layout;
 mod style;
 mod template;
 mod types;
+mod wrap;
 
+pub mod backend;
+pub mod clock;
+pub mod command;
+pub mod dispatcher;
 pub mod elements;
 pub mod event;
+pub mod history;
+#[cfg(feature = "markdown")]
+pub mod markdown;
+pub mod markup;
+pub mod measure;
+pub mod middleware;
+#[cfg(feature = "persist")]
+pub mod persist;
+pub mod record;
 pub mod render;
+pub mod runner;
+pub mod state;
+pub mod subscription;
 pub mod vnode;
 
 pub use app::App;
-pub use render::render_list;
+pub use command::Command;
+pub use dispatcher::Dispatcher;
+pub use layout::{Direction, Media, Overflow, Override};
+pub use markup::{Bindings, Markup};
+pub use style::{Color, TextStyle, Weight};
+pub use render::{render_list, render_list_with};
+pub use runner::{run, Runner};
+pub use subscription::Subscription;
 pub use template::Template;
+#[allow(deprecated)]
 pub use vnode::builder::Builder;
+pub use vnode::builder::{ContainerBuilder, ItemBuilder, TemplateBuilder, TextBuilder};
+pub use vnode::Span;
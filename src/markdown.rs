@@ -0,0 +1,234 @@
+use std::mem;
+use std::sync::Arc;
+
+use pulldown_cmark::{CodeBlockKind, Event, Parser, Tag, TagEnd};
+
+use layout::Direction;
+use style::{TextStyle, Weight};
+use vnode::builder::{ContainerBuilder, ItemBuilder, TextBuilder};
+use vnode::{DynamicNode, Span};
+
+//
+// # Elements
+//
+// Blocks become containers named after them, so they can be styled by name
+// and class:
+//
+// - `markdown` holds the whole document.
+// - `heading` has a class for its level, `h1` to `h6`.
+// - `paragraph`, `quote` and `rule`.
+// - `code` holds a code block as one unwrapped text, with a `lang-<name>`
+//   class for fenced blocks that name their language.
+// - `list` has the class `ordered` or `bullet`. Each `item` in it holds its
+//   marker text and a `body` with the item's blocks.
+//
+// Text inside a block is rich text. Its spans have a class for each inline
+// element they are in, and a style to go with it:
+//
+// - `em` is italic and `strong` bold.
+// - `link` is underlined.
+// - `code` is inline code, left unstyled since terminals have no other font.
+//
+
+pub type LinkFn<A> = dyn Fn(&str) -> A;
+
+/// Converts Markdown into a subtree of nodes.
+pub fn markdown<S, M, A: 'static>(source: &str) -> ContainerBuilder<S, M, A> {
+    Converter::new(None).convert(source)
+}
+
+/// Like `markdown`, where clicking a link dispatches what `on_link` returns
+/// for its destination.
+pub fn markdown_with_links<S, M, A, F>(source: &str, on_link: F) -> ContainerBuilder<S, M, A>
+where
+    A: 'static,
+    F: Fn(&str) -> A + 'static,
+{
+    Converter::new(Some(Arc::new(on_link))).convert(source)
+}
+
+struct Converter<S, M, A> {
+    on_link: Option<Arc<LinkFn<A>>>,
+    // Containers that are still open, innermost last.
+    blocks: Vec<ContainerBuilder<S, M, A>>,
+    spans: Vec<Span<A>>,
+    // The inline elements that are open, innermost last.
+    inline: Vec<(&'static str, TextStyle)>,
+    link: Option<String>,
+    // The next number of each open list, `None` for bullet lists.
+    lists: Vec<Option<u64>>,
+    code: Option<String>,
+}
+
+impl<S, M, A: 'static> Converter<S, M, A> {
+    fn new(on_link: Option<Arc<LinkFn<A>>>) -> Self {
+        Converter {
+            on_link,
+            blocks: Vec::new(),
+            spans: Vec::new(),
+            inline: Vec::new(),
+            link: None,
+            lists: Vec::new(),
+            code: None,
+        }
+    }
+
+    fn convert(mut self, source: &str) -> ContainerBuilder<S, M, A> {
+        self.blocks.push(column("markdown"));
+
+        for event in Parser::new(source) {
+            match event {
+                Event::Start(tag) => self.start(tag),
+                Event::End(tag) => self.end(tag),
+                Event::Text(text) => self.text(&text),
+                Event::Code(text) => {
+                    self.push_inline("code", |_| {});
+                    self.text(&text);
+                    self.inline.pop();
+                }
+                Event::SoftBreak => self.text(" "),
+                Event::HardBreak => self.text("\n"),
+                Event::Rule => {
+                    self.flush();
+                    self.add(ItemBuilder::new("rule"));
+                }
+                _ => {}
+            }
+        }
+
+        self.flush();
+        self.blocks.pop().unwrap()
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => self.open(ContainerBuilder::new("paragraph")),
+            Tag::Heading { level, .. } => {
+                self.open(ContainerBuilder::new("heading").class(level.to_string()))
+            }
+            Tag::BlockQuote(_) => self.open(column("quote")),
+            Tag::CodeBlock(kind) => {
+                let mut code = ContainerBuilder::new("code");
+                if let CodeBlockKind::Fenced(info) = kind {
+                    if let Some(language) = info.split_whitespace().next() {
+                        code = code.class(format!("lang-{}", language));
+                    }
+                }
+
+                self.open(code);
+                self.code = Some(String::new());
+            }
+            Tag::List(first) => {
+                let class = if first.is_some() { "ordered" } else { "bullet" };
+                self.open(column("list").class(class));
+                self.lists.push(first);
+            }
+            Tag::Item => {
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}. ", *number - 1)
+                    }
+                    _ => "\u{2022} ".to_string(),
+                };
+
+                self.open(ContainerBuilder::new("item").child(TextBuilder::new(marker)));
+                self.open(column("body"));
+            }
+            Tag::Emphasis => self.push_inline("em", |style| style.italic = true),
+            Tag::Strong => self.push_inline("strong", |style| style.weight = Weight::Bold),
+            Tag::Link { dest_url, .. } => {
+                self.push_inline("link", |style| style.underline = true);
+                self.link = Some(dest_url.to_string());
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph | TagEnd::Heading(_) | TagEnd::BlockQuote(_) => self.close(),
+            TagEnd::CodeBlock => {
+                let code = self.code.take().unwrap_or_default();
+                self.add(TextBuilder::new(code.trim_end_matches('\n').to_string()).wrap(false));
+                self.close();
+            }
+            TagEnd::List(_) => {
+                self.lists.pop();
+                self.close();
+            }
+            TagEnd::Item => {
+                self.close();
+                self.close();
+            }
+            TagEnd::Emphasis | TagEnd::Strong => {
+                self.inline.pop();
+            }
+            TagEnd::Link => {
+                self.inline.pop();
+                self.link = None;
+            }
+            _ => {}
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if let Some(ref mut code) = self.code {
+            code.push_str(text);
+            return;
+        }
+
+        let style = self.style();
+        let mut span = Span::new(text.to_string()).with_style(style);
+        for (class, _) in &self.inline {
+            span = span.class(*class);
+        }
+
+        if let (Some(url), Some(on_link)) = (self.link.clone(), self.on_link.clone()) {
+            span = span.on_click(move |_| on_link(&url));
+        }
+
+        self.spans.push(span);
+    }
+
+    fn style(&self) -> TextStyle {
+        self.inline
+            .last()
+            .map(|(_, style)| *style)
+            .unwrap_or_default()
+    }
+
+    fn push_inline<F: FnOnce(&mut TextStyle)>(&mut self, class: &'static str, change: F) {
+        let mut style = self.style();
+        change(&mut style);
+        self.inline.push((class, style));
+    }
+
+    fn open(&mut self, block: ContainerBuilder<S, M, A>) {
+        self.flush();
+        self.blocks.push(block);
+    }
+
+    fn close(&mut self) {
+        self.flush();
+        let block = self.blocks.pop().unwrap();
+        self.add(block);
+    }
+
+    fn add<T: Into<DynamicNode<S, M, A>>>(&mut self, child: T) {
+        let parent = self.blocks.pop().unwrap();
+        self.blocks.push(parent.child(child));
+    }
+
+    /// Adds the text collected since the last block started or ended.
+    fn flush(&mut self) {
+        if !self.spans.is_empty() {
+            let spans = mem::take(&mut self.spans);
+            self.add(TextBuilder::rich(spans));
+        }
+    }
+}
+
+fn column<S, M, A>(name: &'static str) -> ContainerBuilder<S, M, A> {
+    ContainerBuilder::new(name).direction(Direction::Column)
+}
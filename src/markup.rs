@@ -0,0 +1,541 @@
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use template::Template;
+use vnode::builder::{ContainerBuilder, ItemBuilder, TextBuilder};
+use vnode::DynamicNode;
+
+//
+// # Bindings
+//
+
+pub type AccessorFn<S> = dyn Fn(&S) -> String;
+
+/// Named accessors that markup placeholders like `{store.points}` resolve to.
+pub struct Bindings<S> {
+    accessors: HashMap<String, Box<AccessorFn<S>>>,
+}
+
+impl<S> Default for Bindings<S> {
+    fn default() -> Bindings<S> {
+        Bindings::new()
+    }
+}
+
+impl<S> Bindings<S> {
+    pub fn new() -> Self {
+        Bindings {
+            accessors: HashMap::new(),
+        }
+    }
+
+    pub fn bind<T, F>(mut self, name: &str, accessor: F) -> Self
+    where
+        T: fmt::Display,
+        F: Fn(&S) -> T + 'static,
+    {
+        self.accessors.insert(
+            name.to_string(),
+            Box::new(move |store: &S| accessor(store).to_string()),
+        );
+
+        self
+    }
+
+    fn contains(&self, name: &str) -> bool {
+        self.accessors.contains_key(name)
+    }
+
+    fn resolve(&self, name: &str, store: &S) -> String {
+        match self.accessors.get(name) {
+            Some(accessor) => accessor(store),
+            None => String::new(),
+        }
+    }
+}
+
+//
+// # Errors
+//
+
+#[derive(Debug)]
+pub enum MarkupError {
+    Io(io::Error),
+    Syntax {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    UnknownBinding(String),
+    NoSource,
+}
+
+impl fmt::Display for MarkupError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MarkupError::Io(err) => write!(f, "Could not read markup: {}", err),
+            MarkupError::Syntax {
+                line,
+                column,
+                message,
+            } => write!(f, "Markup syntax error at {}:{}: {}", line, column, message),
+            MarkupError::UnknownBinding(name) => write!(f, "Unknown binding {{{}}}", name),
+            MarkupError::NoSource => write!(f, "Markup was not loaded from a file"),
+        }
+    }
+}
+
+impl error::Error for MarkupError {}
+
+impl From<io::Error> for MarkupError {
+    fn from(err: io::Error) -> MarkupError {
+        MarkupError::Io(err)
+    }
+}
+
+//
+// # Syntax tree
+//
+
+#[derive(Debug, PartialEq)]
+enum Segment {
+    Literal(String),
+    Placeholder(String),
+}
+
+type Segments = Vec<Segment>;
+
+#[derive(Debug, PartialEq)]
+enum MarkupNode {
+    Text(Segments),
+    Item(Element),
+    Container(Element, Vec<MarkupNode>),
+}
+
+#[derive(Debug, PartialEq)]
+struct Element {
+    name: String,
+    attributes: Vec<(String, Segments)>,
+}
+
+impl MarkupNode {
+    fn check<S>(&self, bindings: &Bindings<S>) -> Result<(), MarkupError> {
+        let check_segments = |segments: &Segments| -> Result<(), MarkupError> {
+            for segment in segments {
+                if let Segment::Placeholder(name) = segment {
+                    if !bindings.contains(name) {
+                        return Err(MarkupError::UnknownBinding(name.clone()));
+                    }
+                }
+            }
+            Ok(())
+        };
+
+        match self {
+            MarkupNode::Text(segments) => check_segments(segments),
+            MarkupNode::Item(element) => {
+                for (_, value) in &element.attributes {
+                    check_segments(value)?;
+                }
+                Ok(())
+            }
+            MarkupNode::Container(element, children) => {
+                for (_, value) in &element.attributes {
+                    check_segments(value)?;
+                }
+                for child in children {
+                    child.check(bindings)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn build<S, M, A>(&self, bindings: &Bindings<S>, store: &S) -> DynamicNode<S, M, A> {
+        let interpolate = |segments: &Segments| -> String {
+            segments
+                .iter()
+                .map(|segment| match segment {
+                    Segment::Literal(text) => text.clone(),
+                    Segment::Placeholder(name) => bindings.resolve(name, store),
+                })
+                .collect()
+        };
+
+        let attributes = |element: &Element| -> Vec<(String, String)> {
+            element
+                .attributes
+                .iter()
+                .map(|(name, value)| (name.clone(), interpolate(value)))
+                .collect()
+        };
+
+        match self {
+            MarkupNode::Text(segments) => TextBuilder::new(interpolate(segments)).done(),
+            MarkupNode::Item(element) => {
+                let mut builder = ItemBuilder::new(element.name.clone());
+                for (name, value) in attributes(element) {
+                    builder = match name.as_ref() {
+                        "id" => builder.id(value),
+                        "key" => builder.key(value),
+                        "class" => builder.classes(value),
+                        _ => builder.attr(name, value),
+                    };
+                }
+                builder.done()
+            }
+            MarkupNode::Container(element, children) => {
+                let mut builder = ContainerBuilder::new(element.name.clone());
+                for (name, value) in attributes(element) {
+                    builder = match name.as_ref() {
+                        "id" => builder.id(value),
+                        "key" => builder.key(value),
+                        "class" => builder.classes(value),
+                        _ => builder.attr(name, value),
+                    };
+                }
+                for child in children {
+                    builder = builder.child(child.build(bindings, store));
+                }
+                builder.done()
+            }
+        }
+    }
+}
+
+//
+// # Parser
+//
+// Containers and text are written the way `vnode::format` writes them, but
+// items have to be self-closing tags (`<icon/>`) where `format` leaves them
+// open (`<icon>`). Text and attribute values may contain `{name}`
+// placeholders (`{{` and `}}` escape braces).
+//
+
+struct Parser<'a> {
+    source: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(source: &'a str) -> Self {
+        Parser {
+            source,
+            position: 0,
+        }
+    }
+
+    fn error<T, E: Into<String>>(&self, message: E) -> Result<T, MarkupError> {
+        self.error_at(self.position, message)
+    }
+
+    fn error_at<T, E: Into<String>>(&self, position: usize, message: E) -> Result<T, MarkupError> {
+        let consumed = &self.source[..position];
+        let line = consumed.matches('\n').count() + 1;
+        let column = match consumed.rfind('\n') {
+            Some(newline) => consumed[newline + 1..].chars().count() + 1,
+            None => consumed.chars().count() + 1,
+        };
+
+        Err(MarkupError::Syntax {
+            line,
+            column,
+            message: message.into(),
+        })
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.source[self.position..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn eat(&mut self, expected: &str) -> bool {
+        if self.rest().starts_with(expected) {
+            self.position += expected.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), MarkupError> {
+        if self.eat(expected) {
+            Ok(())
+        } else {
+            self.error(format!("Expected `{}`", expected))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let trimmed = self.rest().trim_start();
+        self.position = self.source.len() - trimmed.len();
+    }
+
+    fn take_while<P: Fn(char) -> bool>(&mut self, predicate: P) -> &'a str {
+        let rest = self.rest();
+        let end = rest.find(|c| !predicate(c)).unwrap_or(rest.len());
+        self.position += end;
+        &rest[..end]
+    }
+
+    fn parse_document(&mut self) -> Result<MarkupNode, MarkupError> {
+        let mut nodes = self.parse_nodes(None)?;
+
+        match nodes.len() {
+            0 => self.error("Expected a root node"),
+            1 => Ok(nodes.remove(0)),
+            _ => self.error("Expected a single root node"),
+        }
+    }
+
+    fn parse_nodes(&mut self, parent: Option<&str>) -> Result<Vec<MarkupNode>, MarkupError> {
+        let mut nodes = Vec::new();
+
+        loop {
+            self.skip_comments();
+
+            if self.rest().is_empty() {
+                return match parent {
+                    Some(name) => self.error(format!("Missing closing tag for `{}`", name)),
+                    None => Ok(nodes),
+                };
+            }
+
+            if self.rest().starts_with("</") {
+                return match parent {
+                    Some(name) => {
+                        self.expect("</")?;
+                        let closing = self.parse_name()?;
+                        if closing != name {
+                            return self.error(format!(
+                                "Expected closing tag for `{}`, found `{}`",
+                                name, closing
+                            ));
+                        }
+                        self.skip_whitespace();
+                        self.expect(">")?;
+                        Ok(nodes)
+                    }
+                    None => self.error("Unexpected closing tag"),
+                };
+            }
+
+            if self.peek() == Some('<') {
+                nodes.push(self.parse_element()?);
+            } else {
+                let raw = self.take_while(|c| c != '<');
+                let text = raw.trim();
+                if !text.is_empty() {
+                    nodes.push(MarkupNode::Text(self.parse_segments(text)?));
+                }
+            }
+        }
+    }
+
+    fn skip_comments(&mut self) {
+        loop {
+            let before = self.position;
+            self.skip_whitespace();
+            if self.eat("<!--") {
+                match self.rest().find("-->") {
+                    Some(end) => self.position += end + 3,
+                    None => self.position = self.source.len(),
+                }
+            } else {
+                self.position = before;
+                return;
+            }
+        }
+    }
+
+    fn parse_name(&mut self) -> Result<&'a str, MarkupError> {
+        let name = self.take_while(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == '.');
+        if name.is_empty() {
+            self.error("Expected a name")
+        } else {
+            Ok(name)
+        }
+    }
+
+    fn parse_element(&mut self) -> Result<MarkupNode, MarkupError> {
+        self.expect("<")?;
+        let name = self.parse_name()?;
+        let mut attributes = Vec::new();
+
+        loop {
+            self.skip_whitespace();
+
+            if self.eat("/>") {
+                return Ok(MarkupNode::Item(Element {
+                    name: name.to_string(),
+                    attributes,
+                }));
+            }
+
+            if self.eat(">") {
+                let children = self.parse_nodes(Some(name))?;
+                return Ok(MarkupNode::Container(
+                    Element {
+                        name: name.to_string(),
+                        attributes,
+                    },
+                    children,
+                ));
+            }
+
+            let attr_name = self.parse_name()?.to_string();
+            self.skip_whitespace();
+
+            let value = if self.eat("=") {
+                self.skip_whitespace();
+                self.expect("\"")?;
+                let raw = self.take_while(|c| c != '"');
+                let value = self.parse_segments(raw)?;
+                self.expect("\"")?;
+                value
+            } else {
+                Vec::new()
+            };
+
+            attributes.push((attr_name, value));
+        }
+    }
+
+    /// Splits `raw`, a slice of the source, into literals and placeholders.
+    fn parse_segments(&self, raw: &'a str) -> Result<Segments, MarkupError> {
+        let start = raw.as_ptr() as usize - self.source.as_ptr() as usize;
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut position = 0;
+
+        while let Some(c) = raw[position..].chars().next() {
+            let next = &raw[position + c.len_utf8()..];
+
+            match c {
+                '{' if next.starts_with('{') => {
+                    literal.push('{');
+                    position += 2;
+                }
+                '}' if next.starts_with('}') => {
+                    literal.push('}');
+                    position += 2;
+                }
+                '{' => {
+                    let end = match next.find('}') {
+                        Some(end) => position + 1 + end,
+                        None => return self.error_at(start + position, "Unclosed placeholder"),
+                    };
+                    let name = raw[position + 1..end].trim();
+                    if name.is_empty() {
+                        return self.error_at(start + position, "Empty placeholder");
+                    }
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(literal.split_off(0)));
+                    }
+                    segments.push(Segment::Placeholder(name.to_string()));
+                    position = end + 1;
+                }
+                '}' => return self.error_at(start + position, "Unmatched `}`"),
+                _ => {
+                    literal.push(c);
+                    position += c.len_utf8();
+                }
+            }
+        }
+
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(segments)
+    }
+}
+
+fn parse<S>(source: &str, bindings: &Bindings<S>) -> Result<MarkupNode, MarkupError> {
+    let root = Parser::new(source).parse_document()?;
+    root.check(bindings)?;
+    Ok(root)
+}
+
+//
+// # Markup
+//
+
+struct MarkupInner<S> {
+    path: Option<PathBuf>,
+    bindings: Bindings<S>,
+    root: RwLock<MarkupNode>,
+}
+
+/// A view structure parsed at runtime. Cloning returns a handle to the same
+/// markup, so reloading it is visible to every template created from it.
+pub struct Markup<S> {
+    inner: Arc<MarkupInner<S>>,
+}
+
+impl<S> Clone for Markup<S> {
+    fn clone(&self) -> Self {
+        Markup {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<S: 'static> Markup<S> {
+    pub fn parse(source: &str, bindings: Bindings<S>) -> Result<Self, MarkupError> {
+        let root = parse(source, &bindings)?;
+
+        Ok(Markup {
+            inner: Arc::new(MarkupInner {
+                path: None,
+                bindings,
+                root: RwLock::new(root),
+            }),
+        })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P, bindings: Bindings<S>) -> Result<Self, MarkupError> {
+        let source = fs::read_to_string(path.as_ref())?;
+        let root = parse(&source, &bindings)?;
+
+        Ok(Markup {
+            inner: Arc::new(MarkupInner {
+                path: Some(path.as_ref().to_path_buf()),
+                bindings,
+                root: RwLock::new(root),
+            }),
+        })
+    }
+
+    /// Replaces the structure with `source`. On error the previous structure is kept.
+    pub fn update(&self, source: &str) -> Result<(), MarkupError> {
+        let root = parse(source, &self.inner.bindings)?;
+        *self.inner.root.write().unwrap() = root;
+        Ok(())
+    }
+
+    /// Reads the file this markup was loaded from again.
+    pub fn reload(&self) -> Result<(), MarkupError> {
+        match self.inner.path {
+            Some(ref path) => self.update(&fs::read_to_string(path)?),
+            None => Err(MarkupError::NoSource),
+        }
+    }
+
+    pub fn template<M, A>(&self) -> Arc<Template<S, M, A>> {
+        let inner = self.inner.clone();
+
+        Template::new(move |store: &S, _message: &Option<M>| {
+            let root = inner.root.read().unwrap();
+            root.build(&inner.bindings, store)
+        })
+    }
+}
@@ -0,0 +1,127 @@
+use std::cmp::min;
+use std::collections::HashMap;
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use render::{clamp, Size};
+
+/// Measures text in the units a backend draws in, e.g. terminal cells or
+/// pixels. All sizing of `Text` nodes goes through one.
+pub trait TextMeasurer {
+    /// Size of `text` laid out on a single line.
+    fn measure(&self, text: &str) -> Size;
+
+    /// Length in bytes of the longest start of `text` that is at most
+    /// `width` wide.
+    fn fit(&self, text: &str, width: u16) -> usize {
+        let mut end = 0;
+
+        for (index, character) in text.char_indices() {
+            let next = index + character.len_utf8();
+            if self.measure(&text[..next]).0 > width {
+                break;
+            }
+            end = next;
+        }
+
+        end
+    }
+}
+
+impl<T: TextMeasurer + ?Sized> TextMeasurer for Box<T> {
+    fn measure(&self, text: &str) -> Size {
+        (**self).measure(text)
+    }
+
+    fn fit(&self, text: &str, width: u16) -> usize {
+        (**self).fit(text, width)
+    }
+}
+
+/// Measures in terminal cells, one line high. Text is split into grapheme
+/// clusters, each one or two cells wide following East Asian width rules,
+/// so combining marks take no cell of their own.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TerminalMeasurer;
+
+impl TextMeasurer for TerminalMeasurer {
+    fn measure(&self, text: &str) -> Size {
+        let width = text
+            .graphemes(true)
+            .map(|grapheme| grapheme_width(grapheme) as usize)
+            .sum();
+        (clamp(width), 1)
+    }
+
+    fn fit(&self, text: &str, width: u16) -> usize {
+        let mut used = 0u16;
+
+        for (index, grapheme) in text.grapheme_indices(true) {
+            used = used.saturating_add(grapheme_width(grapheme));
+            if used > width {
+                return index;
+            }
+        }
+
+        text.len()
+    }
+}
+
+/// Cells a grapheme cluster takes in a terminal. Emoji sequences made of
+/// several wide characters still only take two.
+pub fn grapheme_width(grapheme: &str) -> u16 {
+    min(UnicodeWidthStr::width(grapheme), 2) as u16
+}
+
+/// Measures in pixels for a bitmap font, where every glyph has its own
+/// advance and all lines are equally high.
+#[derive(Clone, Debug)]
+pub struct BitmapFont {
+    advance: u16,
+    line_height: u16,
+    glyphs: HashMap<char, u16>,
+}
+
+impl BitmapFont {
+    /// A font where every glyph is `advance` pixels wide.
+    pub fn new(advance: u16, line_height: u16) -> Self {
+        BitmapFont {
+            advance,
+            line_height,
+            glyphs: HashMap::new(),
+        }
+    }
+
+    /// Gives `glyph` its own advance.
+    pub fn glyph(mut self, glyph: char, advance: u16) -> Self {
+        self.glyphs.insert(glyph, advance);
+        self
+    }
+
+    fn advance(&self, glyph: char) -> u16 {
+        self.glyphs.get(&glyph).cloned().unwrap_or(self.advance)
+    }
+}
+
+impl TextMeasurer for BitmapFont {
+    fn measure(&self, text: &str) -> Size {
+        let width = text.chars().fold(0u16, |width, glyph| {
+            width.saturating_add(self.advance(glyph))
+        });
+        (width, self.line_height)
+    }
+
+    fn fit(&self, text: &str, width: u16) -> usize {
+        let mut used = 0u16;
+
+        for (index, glyph) in text.char_indices() {
+            used = used.saturating_add(self.advance(glyph));
+            if used > width {
+                return index;
+            }
+        }
+
+        text.len()
+    }
+}
@@ -0,0 +1,53 @@
+use std::fmt::Debug;
+use std::io::Write;
+
+pub type Next<'a, S, A> = &'a mut dyn FnMut(S, A) -> S;
+
+/// Wraps the reducer of an `App`. A middleware sees the action and the store
+/// before passing them on with `next`, and the resulting store afterwards.
+/// It can change the action, or reject it by returning the store untouched.
+pub trait Middleware<S, A> {
+    fn dispatch(&mut self, store: S, action: A, next: Next<S, A>) -> S;
+}
+
+impl<S, A, F> Middleware<S, A> for F
+where
+    F: FnMut(S, A, Next<S, A>) -> S,
+{
+    fn dispatch(&mut self, store: S, action: A, next: Next<S, A>) -> S {
+        self(store, action, next)
+    }
+}
+
+/// Writes every dispatched action to `writer`, one per line.
+pub struct Logger<W> {
+    writer: W,
+}
+
+impl<W: Write> Logger<W> {
+    pub fn new(writer: W) -> Self {
+        Logger { writer }
+    }
+}
+
+impl<S, A: Debug, W: Write> Middleware<S, A> for Logger<W> {
+    fn dispatch(&mut self, store: S, action: A, next: Next<S, A>) -> S {
+        // A failing log must not stop the app.
+        let _ = writeln!(self.writer, "{:?}", action);
+        next(store, action)
+    }
+}
+
+pub(crate) fn run<S, A>(
+    middleware: &mut [Box<dyn Middleware<S, A>>],
+    store: S,
+    action: A,
+    reducer: Next<S, A>,
+) -> S {
+    match middleware.split_first_mut() {
+        Some((first, rest)) => first.dispatch(store, action, &mut |store, action| {
+            run(rest, store, action, reducer)
+        }),
+        None => reducer(store, action),
+    }
+}
@@ -0,0 +1,106 @@
+use std::error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io;
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{self, Value};
+
+//
+// # Snapshots
+//
+// A snapshot is a JSON object holding the version the store was saved with
+// and the serialized store: `{"version": 2, "store": {...}}`.
+//
+
+pub type Version = u32;
+
+#[derive(Debug)]
+pub enum PersistError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    InvalidSnapshot,
+    UnsupportedVersion(Version),
+}
+
+impl fmt::Display for PersistError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PersistError::Io(err) => write!(f, "Could not access snapshot: {}", err),
+            PersistError::Json(err) => write!(f, "Could not convert snapshot: {}", err),
+            PersistError::InvalidSnapshot => write!(f, "Snapshot is missing its version or store"),
+            PersistError::UnsupportedVersion(version) => {
+                write!(f, "Snapshot version {} is not supported", version)
+            }
+        }
+    }
+}
+
+impl error::Error for PersistError {}
+
+impl From<io::Error> for PersistError {
+    fn from(err: io::Error) -> PersistError {
+        PersistError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for PersistError {
+    fn from(err: serde_json::Error) -> PersistError {
+        PersistError::Json(err)
+    }
+}
+
+/// Writes `store` to `path`, tagged with `version`. The file is replaced
+/// only once the snapshot was written completely.
+pub fn save<S: Serialize, P: AsRef<Path>>(
+    path: P,
+    version: Version,
+    store: &S,
+) -> Result<(), PersistError> {
+    let path = path.as_ref();
+    let partial = path.with_extension("partial");
+
+    let snapshot = json!({
+        "version": version,
+        "store": serde_json::to_value(store)?,
+    });
+
+    serde_json::to_writer(File::create(&partial)?, &snapshot)?;
+    fs::rename(&partial, path)?;
+
+    Ok(())
+}
+
+/// Reads a store saved with `save`. Snapshots of an older version are
+/// passed to `migrate` along with their version first, which has to return
+/// the store in the shape of the current `version`.
+pub fn load<S, P, F>(path: P, version: Version, migrate: F) -> Result<S, PersistError>
+where
+    S: DeserializeOwned,
+    P: AsRef<Path>,
+    F: Fn(Version, Value) -> Result<Value, PersistError>,
+{
+    let snapshot: Value = serde_json::from_reader(File::open(path)?)?;
+
+    let saved = snapshot
+        .get("version")
+        .and_then(Value::as_u64)
+        .ok_or(PersistError::InvalidSnapshot)? as Version;
+
+    let store = snapshot
+        .get("store")
+        .cloned()
+        .ok_or(PersistError::InvalidSnapshot)?;
+
+    let store = if saved == version {
+        store
+    } else if saved < version {
+        migrate(saved, store)?
+    } else {
+        return Err(PersistError::UnsupportedVersion(saved));
+    };
+
+    Ok(serde_json::from_value(store)?)
+}
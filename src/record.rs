@@ -0,0 +1,141 @@
+use std::error;
+use std::fmt::{self, Display};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use clock::{Clock, SystemClock};
+use middleware::{Middleware, Next};
+
+//
+// # Recording
+//
+// Logs have one action per line: milliseconds since recording started, a
+// tab, and the action's `Display` output, which has to fit on one line and
+// parse back with `FromStr`.
+//
+
+/// Middleware that writes every action it sees to an action log. Add it
+/// before other middleware to record actions exactly as dispatched.
+pub struct Recorder<W> {
+    writer: W,
+    clock: Box<dyn Clock>,
+    started: Instant,
+}
+
+impl Recorder<File> {
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Recorder::new(File::create(path)?))
+    }
+}
+
+impl<W: Write> Recorder<W> {
+    pub fn new(writer: W) -> Self {
+        Recorder::with_clock(writer, SystemClock)
+    }
+
+    pub fn with_clock(writer: W, clock: impl Clock + 'static) -> Self {
+        let started = clock.now();
+
+        Recorder {
+            writer,
+            clock: Box::new(clock),
+            started,
+        }
+    }
+}
+
+impl<S, A: Display, W: Write> Middleware<S, A> for Recorder<W> {
+    fn dispatch(&mut self, store: S, action: A, next: Next<S, A>) -> S {
+        let elapsed = self.clock.now() - self.started;
+        let millis = elapsed.as_secs() * 1000 + u64::from(elapsed.subsec_millis());
+
+        // A failing recording must not stop the app.
+        let _ = writeln!(self.writer, "{}\t{}", millis, action);
+        let _ = self.writer.flush();
+
+        next(store, action)
+    }
+}
+
+//
+// # Replaying
+//
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    Parse { line: usize, content: String },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::Io(err) => write!(f, "Could not read action log: {}", err),
+            ReplayError::Parse { line, content } => {
+                write!(f, "Invalid action log entry at line {}: {}", line, content)
+            }
+        }
+    }
+}
+
+impl error::Error for ReplayError {}
+
+impl From<io::Error> for ReplayError {
+    fn from(err: io::Error) -> ReplayError {
+        ReplayError::Io(err)
+    }
+}
+
+/// Actions read back from a log written by `Recorder`, with the time they
+/// were dispatched at relative to the start of the recording.
+pub struct ActionLog<A> {
+    entries: Vec<(Duration, A)>,
+}
+
+impl<A: FromStr> ActionLog<A> {
+    pub fn read<R: Read>(reader: R) -> Result<Self, ReplayError> {
+        let mut entries = Vec::new();
+
+        for (index, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line?;
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut parts = line.splitn(2, '\t');
+            let millis = parts.next().and_then(|millis| millis.parse::<u64>().ok());
+            let action = parts.next().and_then(|action| action.parse::<A>().ok());
+
+            match (millis, action) {
+                (Some(millis), Some(action)) => {
+                    entries.push((Duration::from_millis(millis), action))
+                }
+                _ => {
+                    return Err(ReplayError::Parse {
+                        line: index + 1,
+                        content: line,
+                    })
+                }
+            }
+        }
+
+        Ok(ActionLog { entries })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ReplayError> {
+        ActionLog::read(File::open(path)?)
+    }
+}
+
+impl<A> ActionLog<A> {
+    pub fn entries(&self) -> &[(Duration, A)] {
+        &self.entries
+    }
+
+    pub fn into_actions(self) -> Vec<A> {
+        self.entries.into_iter().map(|(_, action)| action).collect()
+    }
+}
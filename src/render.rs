@@ -1,8 +1,19 @@
-use vnode::static_node::{StaticItem, StaticNode, StaticText};
+use std::cmp::min;
+
+use event::Handlers;
+use layout::{Direction, Overflow};
+use measure::{TerminalMeasurer, TextMeasurer};
+use style::TextStyle;
+use vnode::static_node::{StaticContainer, StaticItem, StaticNode, StaticText};
+use vnode::{Span, Text, TextContent};
+use wrap::{self, Piece, ELLIPSIS};
 
 pub type Position = (u16, u16);
 pub type Size = (u16, u16);
 
+/// The viewport templates see until the real one is known.
+pub const DEFAULT_VIEWPORT: Size = (80, 24);
+
 pub enum RenderItem<A> {
     Text(StaticText<A>),
     Item(StaticItem<A>),
@@ -12,6 +23,32 @@ pub struct RenderText<A> {
     pub position: Position,
     pub size: Size,
     pub node: StaticText<A>,
+    /// The part of the node's content this run shows.
+    pub content: TextContent,
+    /// Index of the node's span the run belongs to, for rich text.
+    pub span: Option<usize>,
+    /// Handlers of the innermost clickable container around the node.
+    pub enclosing: Option<Handlers<A>>,
+}
+
+impl<A> RenderText<A> {
+    pub fn span(&self) -> Option<&Span<A>> {
+        self.span.map(|span| &self.node.spans()[span])
+    }
+
+    pub fn style(&self) -> TextStyle {
+        self.span().map(|span| span.style()).unwrap_or_default()
+    }
+
+    /// The handlers of the run's span if it is clickable, otherwise those of
+    /// the node, or of the container around it if the node is not either.
+    pub fn handlers(&self) -> &Handlers<A> {
+        match (self.span(), &self.enclosing) {
+            (Some(span), _) if span.handlers().clickable() => span.handlers(),
+            (_, Some(enclosing)) if !self.node.handlers().clickable() => enclosing,
+            _ => self.node.handlers(),
+        }
+    }
 }
 
 pub enum RenderCommand<A> {
@@ -19,33 +56,269 @@ pub enum RenderCommand<A> {
 }
 
 impl<A> RenderCommand<A> {
-    fn text(text: StaticText<A>, parent_position: Position, parent_size: Size) -> Self {
-        let position = parent_position;
-        let size = (text.content().len() as u16, 1 as u16);
-        RenderCommand::Text(RenderText {
-            node: text,
-            position,
-            size,
-        })
+    pub fn position(&self) -> Position {
+        match self {
+            RenderCommand::Text(text) => text.position,
+        }
+    }
+
+    pub fn size(&self) -> Size {
+        match self {
+            RenderCommand::Text(text) => text.size,
+        }
+    }
+
+    pub fn contains(&self, (x, y): Position) -> bool {
+        let (left, top) = self.position();
+        let (width, height) = self.size();
+        x >= left && y >= top && x - left < width && y - top < height
     }
 }
 
 pub type RenderList<A> = Vec<RenderCommand<A>>;
 
+/// Lays `node` out in terminal cells.
 pub fn render_list<A>(
     node: &StaticNode<A>,
     parent_position: Position,
     parent_size: Size,
 ) -> RenderList<A> {
-    let mut list = RenderList::new();
-    match node {
-        StaticNode::Text(text) => list.push(RenderCommand::text(
-            text.clone(),
-            parent_position,
-            (text.content().len() as u16, 1 as u16),
-        )),
-        _ => panic!("Not implemented"),
+    render_list_with(node, parent_position, parent_size, &TerminalMeasurer)
+}
+
+/// Like `render_list`, but text is measured by `measurer`.
+pub fn render_list_with<A>(
+    node: &StaticNode<A>,
+    parent_position: Position,
+    parent_size: Size,
+    measurer: &dyn TextMeasurer,
+) -> RenderList<A> {
+    let mut layout = Layout {
+        viewport: parent_size,
+        measurer,
+        list: RenderList::new(),
+        enclosing: None,
     };
+    layout.node(node, parent_position, parent_size);
+    layout.list
+}
+
+//
+// # Layout
+//
+// Children of a container are placed one after another, left to right in a
+// `Row` and top to bottom in a `Column`. Text wraps within the width it is
+// given. Whatever does not fit is cut off.
+// Responsive rules are matched against `viewport`, the size the whole list
+// is laid out in.
+// Items and containers have no commands of their own, so clicks on them are
+// handled through the text inside, which keeps the handlers of `enclosing`.
+//
+
+struct Layout<'a, A> {
+    viewport: Size,
+    measurer: &'a dyn TextMeasurer,
+    list: RenderList<A>,
+    enclosing: Option<Handlers<A>>,
+}
+
+impl<'a, A> Layout<'a, A> {
+    /// Adds the commands for `node` and returns the size it took.
+    fn node(&mut self, node: &StaticNode<A>, position: Position, size: Size) -> Size {
+        if self.hidden(node) {
+            return (0, 0);
+        }
+
+        match node {
+            StaticNode::Text(text) => self.text(text, position, size),
+            StaticNode::Item(_) => (0, 0),
+            StaticNode::Container(container) => self.container(container, position, size),
+        }
+    }
+
+    fn hidden(&self, node: &StaticNode<A>) -> bool {
+        match node {
+            StaticNode::Text(_) => false,
+            StaticNode::Item(item) => item.layout().hidden(self.viewport),
+            StaticNode::Container(container) => container.item().layout().hidden(self.viewport),
+        }
+    }
+
+    /// Text gets a run per line, or one per span and line for rich text.
+    /// Lines are cut at the edge of the parent as its overflow says, and
+    /// lines below its bottom are left out.
+    fn text(&mut self, text: &StaticText<A>, position: Position, size: Size) -> Size {
+        if size.0 == 0 || size.1 == 0 {
+            return (0, 0);
+        }
+
+        let content = text.content();
+        let mut used: Size = (0, 0);
+        // Wrapped lines already fit, and scrolling them would skip the start
+        // of every line.
+        let overflow = if text.wraps() {
+            Overflow::Clip
+        } else {
+            text.overflow()
+        };
+
+        for line in wrap::lines(content, size.0, text.wraps(), self.measurer) {
+            // Lines are slices of the content, so this is where they start.
+            let offset = line.as_ptr() as usize - content.as_ptr() as usize;
+            let pieces = wrap::truncate(line, size.0, overflow, self.measurer);
+            let runs = runs(text, offset, &pieces);
+
+            let visible: String = runs.iter().map(|(_, run)| run.as_str()).collect();
+            let (width, height) = self.measurer.measure(&visible);
+            let height = min(height, size.1 - used.1);
+
+            if height > 0 {
+                let mut x = position.0;
+
+                for (span, run) in runs {
+                    let run_width = self.measurer.measure(&run).0;
+                    if run_width == 0 {
+                        continue;
+                    }
+
+                    self.list.push(RenderCommand::Text(RenderText {
+                        position: (x, position.1.saturating_add(used.1)),
+                        size: (run_width, height),
+                        node: text.clone(),
+                        content: run.into(),
+                        span,
+                        enclosing: self.enclosing.clone(),
+                    }));
+                    x = x.saturating_add(run_width);
+                }
+            }
+
+            used = (used.0.max(width), used.1 + height);
+            if used.1 == size.1 {
+                break;
+            }
+        }
+
+        used
+    }
+
+    fn container(
+        &mut self,
+        container: &StaticContainer<A>,
+        position: Position,
+        size: Size,
+    ) -> Size {
+        let layout = container
+            .layout()
+            .resolve(container.item().layout(), self.viewport);
+        let (top, right, bottom, left) = layout.padding();
+        let (spacing_x, spacing_y) = layout.spacing();
+
+        let inner = (
+            position.0.saturating_add(clamp(left)),
+            position.1.saturating_add(clamp(top)),
+        );
+        let inner_size = (
+            size.0.saturating_sub(clamp(left + right)),
+            size.1.saturating_sub(clamp(top + bottom)),
+        );
+
+        let enclosing = self.enclosing.clone();
+        if container.item().handlers().clickable() {
+            self.enclosing = Some(container.item().handlers().clone());
+        }
+
+        // Offset along the direction of the container, and the largest extent
+        // across it.
+        let mut along: u16 = 0;
+        let mut across: u16 = 0;
+        let mut placed = 0;
+
+        for child in container.children() {
+            if self.hidden(child) {
+                continue;
+            }
+
+            let used = match layout.direction() {
+                Direction::Row => {
+                    if placed > 0 {
+                        along = min(along.saturating_add(clamp(spacing_x)), inner_size.0);
+                    }
+                    let used = self.node(
+                        child,
+                        (inner.0.saturating_add(along), inner.1),
+                        (inner_size.0 - along, inner_size.1),
+                    );
+                    (used.0, used.1)
+                }
+                Direction::Column => {
+                    if placed > 0 {
+                        along = min(along.saturating_add(clamp(spacing_y)), inner_size.1);
+                    }
+                    let used = self.node(
+                        child,
+                        (inner.0, inner.1.saturating_add(along)),
+                        (inner_size.0, inner_size.1 - along),
+                    );
+                    (used.1, used.0)
+                }
+            };
+
+            along += used.0;
+            across = across.max(used.1);
+            placed += 1;
+        }
+
+        self.enclosing = enclosing;
+
+        let (width, height) = match layout.direction() {
+            Direction::Row => (along, across),
+            Direction::Column => (across, along),
+        };
+
+        (
+            min(width.saturating_add(clamp(left + right)), size.0),
+            min(height.saturating_add(clamp(top + bottom)), size.1),
+        )
+    }
+}
+
+/// Splits the pieces of the line at `offset` into runs of the same span.
+/// An ellipsis belongs to the span before it.
+fn runs<A>(text: &Text<A>, offset: usize, pieces: &[Piece]) -> Vec<(Option<usize>, String)> {
+    let content = text.content();
+    let ranges = text.span_ranges();
+    let span_at = |index: usize| ranges.iter().position(|range| range.contains(&index));
+
+    let mut runs: Vec<(Option<usize>, String)> = Vec::new();
+    let mut push = |span: Option<usize>, part: &str| match runs.last_mut() {
+        Some((last, run)) if *last == span => run.push_str(part),
+        _ => runs.push((span, part.to_string())),
+    };
+
+    let mut last = span_at(offset);
+
+    for piece in pieces {
+        match piece {
+            Piece::Slice(range) => {
+                let mut start = offset + range.start;
+                let end = offset + range.end;
+
+                while start < end {
+                    let span = span_at(start);
+                    let part_end = span.map_or(end, |span| min(ranges[span].end, end));
+                    push(span, &content[start..part_end]);
+                    last = span;
+                    start = part_end;
+                }
+            }
+            Piece::Ellipsis => push(last, ELLIPSIS),
+        }
+    }
+
+    runs
+}
 
-    list
+pub(crate) fn clamp(value: usize) -> u16 {
+    min(value, u16::MAX as usize) as u16
 }
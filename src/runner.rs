@@ -0,0 +1,143 @@
+use std::io;
+use std::time::{Duration, Instant};
+
+use app::App;
+use backend::{Backend, Event};
+use event::ClickEvent;
+use render::{Position, RenderCommand, RenderList};
+use state::LocalAction;
+
+type KeysFn<A> = dyn Fn(char) -> Option<A>;
+type UntilFn<S> = dyn Fn(&S) -> bool;
+
+/// Drives an `App` with a `Backend`: input is dispatched, commands,
+/// subscriptions and dispatchers are polled, and a new frame is presented
+/// whenever the view or the size changed. Stops when the backend sends
+/// `Quit`.
+pub struct Runner<S, M, A> {
+    app: App<S, M, A>,
+    frame: Duration,
+    keys: Option<Box<KeysFn<A>>>,
+    until: Option<Box<UntilFn<S>>>,
+}
+
+impl<S, M, A> Runner<S, M, A> {
+    pub fn new(app: App<S, M, A>) -> Self {
+        Runner {
+            app,
+            frame: Duration::from_millis(16),
+            keys: None,
+            until: None,
+        }
+    }
+
+    /// At most one frame is presented per `frame`. Defaults to 16ms.
+    pub fn frame(mut self, frame: Duration) -> Self {
+        self.frame = frame;
+        self
+    }
+
+    /// Turns key presses into actions.
+    pub fn on_key(mut self, keys: impl Fn(char) -> Option<A> + 'static) -> Self {
+        self.keys = Some(Box::new(keys));
+        self
+    }
+
+    /// Also stops once the store satisfies `until`.
+    pub fn until(mut self, until: impl Fn(&S) -> bool + 'static) -> Self {
+        self.until = Some(Box::new(until));
+        self
+    }
+
+    /// Runs until done and hands the `App` back. Text is measured as the
+    /// backend says, unless the `App` was given a measurer.
+    pub fn run<B: Backend>(mut self, backend: &mut B) -> io::Result<App<S, M, A>> {
+        self.app.default_measurer(backend.measurer());
+        self.app.resize(backend.size()?);
+        let mut presented = self.app.view().clone();
+        backend.present(self.app.layout())?;
+
+        loop {
+            let deadline = Instant::now() + self.frame;
+            let mut resized = false;
+
+            loop {
+                let now = Instant::now();
+                let timeout = if deadline > now {
+                    deadline - now
+                } else {
+                    Duration::from_millis(0)
+                };
+
+                match backend.poll(timeout)? {
+                    Some(Event::Quit) => return Ok(self.app),
+                    Some(Event::Resize(size)) => {
+                        self.app.resize(size);
+                        resized = true;
+                    }
+                    Some(Event::Click(position)) => {
+                        let (action, local) = click(self.app.layout(), position);
+                        if let Some(local) = local {
+                            self.app.dispatch_local(local);
+                        }
+                        if let Some(action) = action {
+                            self.app.enqueue(action);
+                        }
+                    }
+                    Some(Event::Key(key)) => {
+                        if let Some(action) = self.keys.as_ref().and_then(|keys| keys(key)) {
+                            self.app.enqueue(action);
+                        }
+                    }
+                    None => break,
+                }
+
+                if Instant::now() >= deadline {
+                    break;
+                }
+            }
+
+            self.app.tick();
+
+            if resized || !self.app.view().ptr_eq(&presented) {
+                presented = self.app.view().clone();
+                backend.present(self.app.layout())?;
+            }
+
+            if let Some(ref until) = self.until {
+                if until(self.app.store()) {
+                    return Ok(self.app);
+                }
+            }
+        }
+    }
+}
+
+/// Runs `app` on `backend` with the default settings of `Runner`.
+pub fn run<S, M, A, B: Backend>(app: App<S, M, A>, backend: &mut B) -> io::Result<App<S, M, A>> {
+    Runner::new(app).run(backend)
+}
+
+/// The action and local action of the topmost clickable command at
+/// `position`.
+fn click<A>(list: &RenderList<A>, position: Position) -> (Option<A>, Option<LocalAction>) {
+    let handlers = list
+        .iter()
+        .rev()
+        .filter(|command| command.contains(position))
+        .map(|command| match command {
+            RenderCommand::Text(text) => text.handlers(),
+        })
+        .find(|handlers| handlers.clickable());
+
+    match handlers {
+        Some(handlers) => (
+            handlers.click.as_ref().map(|click| click(ClickEvent {})),
+            handlers
+                .local_click
+                .as_ref()
+                .map(|click| click(ClickEvent {})),
+        ),
+        None => (None, None),
+    }
+}
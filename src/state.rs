@@ -0,0 +1,279 @@
+use std::any::{Any, TypeId};
+use std::collections::{HashMap, HashSet};
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use render::Size;
+use vnode::KeyValue;
+
+/// Identifies the local state of one template: its `key` if it was given
+/// one, otherwise its position in the tree.
+///
+/// Keys only have to be unique among siblings, so a key belongs to the path
+/// of the template's parent. Plain strings are keys of children of the root.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum StateKey {
+    Key(Vec<usize>, KeyValue),
+    Path(Vec<usize>),
+}
+
+impl StateKey {
+    /// The key of a child of the node at `parent`.
+    pub fn scoped<T: Into<KeyValue>>(parent: Vec<usize>, key: T) -> StateKey {
+        StateKey::Key(parent, key.into())
+    }
+}
+
+impl From<&'static str> for StateKey {
+    fn from(key: &'static str) -> StateKey {
+        StateKey::scoped(Vec::new(), key)
+    }
+}
+
+impl From<String> for StateKey {
+    fn from(key: String) -> StateKey {
+        StateKey::scoped(Vec::new(), key)
+    }
+}
+
+impl From<Vec<usize>> for StateKey {
+    fn from(path: Vec<usize>) -> StateKey {
+        StateKey::Path(path)
+    }
+}
+
+pub(crate) type LocalReducerFn<L, LA> = dyn Fn(L, LA) -> L;
+
+// A value cached for a node, by its path and type, with the pass it was
+// stored in.
+type Cached = HashMap<(Vec<usize>, TypeId), (usize, Box<dyn Any>)>;
+
+/// An action for the local state of one template, e.g. returned by a
+/// handler set with `on_local_click`.
+pub struct LocalAction {
+    key: StateKey,
+    action: Box<dyn Any>,
+}
+
+impl LocalAction {
+    pub fn new<K: Into<StateKey>, LA: 'static>(key: K, action: LA) -> Self {
+        LocalAction {
+            key: key.into(),
+            action: Box::new(action),
+        }
+    }
+
+    pub fn key(&self) -> &StateKey {
+        &self.key
+    }
+}
+
+/// Given to the renderer of a stateful template to address local actions to
+/// its own state.
+pub struct LocalHandle<LA> {
+    key: StateKey,
+    action: PhantomData<fn(LA)>,
+}
+
+impl<LA> Clone for LocalHandle<LA> {
+    fn clone(&self) -> Self {
+        LocalHandle {
+            key: self.key.clone(),
+            action: PhantomData,
+        }
+    }
+}
+
+impl<LA: 'static> LocalHandle<LA> {
+    pub(crate) fn new(key: StateKey) -> Self {
+        LocalHandle {
+            key,
+            action: PhantomData,
+        }
+    }
+
+    pub fn key(&self) -> &StateKey {
+        &self.key
+    }
+
+    pub fn action(&self, action: LA) -> LocalAction {
+        LocalAction::new(self.key.clone(), action)
+    }
+}
+
+trait Slot {
+    fn state(&self) -> &dyn Any;
+    fn update(&mut self, action: Box<dyn Any>) -> bool;
+}
+
+struct Local<L, LA> {
+    state: Option<L>,
+    reducer: Arc<LocalReducerFn<L, LA>>,
+}
+
+impl<L: 'static, LA: 'static> Slot for Local<L, LA> {
+    fn state(&self) -> &dyn Any {
+        self
+    }
+
+    fn update(&mut self, action: Box<dyn Any>) -> bool {
+        match action.downcast::<LA>() {
+            Ok(action) => {
+                let state = self.state.take().unwrap();
+                self.state = Some((self.reducer)(state, *action));
+                true
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+/// Local state of every stateful template, kept by `App` between renders.
+#[derive(Default)]
+pub(crate) struct LocalStates {
+    slots: HashMap<StateKey, Box<dyn Slot>>,
+    visited: Vec<StateKey>,
+    revision: usize,
+    // Counts the renders that were collected.
+    pass: usize,
+    // Values kept for the node at a path from one render to the next.
+    cached: Cached,
+}
+
+impl LocalStates {
+    pub(crate) fn new() -> Self {
+        Default::default()
+    }
+
+    pub(crate) fn update(&mut self, action: LocalAction) -> bool {
+        let updated = match self.slots.get_mut(&action.key) {
+            Some(slot) => slot.update(action.action),
+            None => false,
+        };
+
+        if updated {
+            self.revision += 1;
+        }
+
+        updated
+    }
+
+    /// Drops the state of templates, and cached values of nodes, that were
+    /// not rendered since the last call.
+    pub(crate) fn collect(&mut self) {
+        let visited: HashSet<StateKey> = self.visited.drain(..).collect();
+        self.slots.retain(|key, _| visited.contains(key));
+
+        let pass = self.pass;
+        self.cached.retain(|_, (stored, _)| *stored == pass);
+        self.pass += 1;
+    }
+}
+
+pub(crate) struct RenderContext<'a> {
+    states: &'a mut LocalStates,
+    viewport: Size,
+    path: Vec<usize>,
+    key: Option<KeyValue>,
+}
+
+impl<'a> RenderContext<'a> {
+    pub(crate) fn new(states: &'a mut LocalStates, viewport: Size) -> Self {
+        RenderContext {
+            states,
+            viewport,
+            path: Vec::new(),
+            key: None,
+        }
+    }
+
+    pub(crate) fn viewport(&self) -> Size {
+        self.viewport
+    }
+
+    pub(crate) fn enter(&mut self, index: usize) {
+        self.path.push(index);
+    }
+
+    pub(crate) fn leave(&mut self) {
+        self.path.pop();
+    }
+
+    pub(crate) fn revision(&self) -> usize {
+        self.states.revision
+    }
+
+    pub(crate) fn set_key(&mut self, key: Option<KeyValue>) -> Option<KeyValue> {
+        ::std::mem::replace(&mut self.key, key)
+    }
+
+    /// Identifies the state of the template being rendered.
+    pub(crate) fn state_key(&self) -> StateKey {
+        match self.key {
+            Some(ref key) => {
+                let parent = &self.path[..self.path.len().saturating_sub(1)];
+                StateKey::scoped(parent.to_vec(), key.clone())
+            }
+            None => StateKey::Path(self.path.clone()),
+        }
+    }
+
+    pub(crate) fn visited(&self) -> usize {
+        self.states.visited.len()
+    }
+
+    pub(crate) fn visited_since(&self, start: usize) -> Vec<StateKey> {
+        self.states.visited[start..].to_vec()
+    }
+
+    pub(crate) fn revisit(&mut self, keys: &[StateKey]) {
+        self.states.visited.extend_from_slice(keys);
+    }
+
+    /// Takes the value of type `T` that was cached for the current path.
+    pub(crate) fn take_cached<T: 'static>(&mut self) -> Option<T> {
+        self.states
+            .cached
+            .remove(&(self.path.clone(), TypeId::of::<T>()))
+            .and_then(|(_, value)| value.downcast::<T>().ok())
+            .map(|value| *value)
+    }
+
+    /// Keeps `value` for the current path until the next render.
+    pub(crate) fn cache<T: 'static>(&mut self, value: T) {
+        let key = (self.path.clone(), TypeId::of::<T>());
+        let pass = self.states.pass;
+        self.states.cached.insert(key, (pass, Box::new(value)));
+    }
+
+    pub(crate) fn state<L: 'static, LA: 'static>(
+        &mut self,
+        init: &dyn Fn() -> L,
+        reducer: &Arc<LocalReducerFn<L, LA>>,
+    ) -> &L {
+        let key = self.state_key();
+
+        let fresh = match self.states.slots.get(&key) {
+            Some(slot) => slot.state().downcast_ref::<Local<L, LA>>().is_none(),
+            None => true,
+        };
+
+        if fresh {
+            self.states.slots.insert(
+                key.clone(),
+                Box::new(Local {
+                    state: Some(init()),
+                    reducer: reducer.clone(),
+                }),
+            );
+        }
+
+        self.states.visited.push(key.clone());
+
+        self.states.slots[&key]
+            .state()
+            .downcast_ref::<Local<L, LA>>()
+            .and_then(|local| local.state.as_ref())
+            .unwrap()
+    }
+}
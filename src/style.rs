@@ -18,3 +18,20 @@ pub struct Style {
     font: Font,
     border: Border,
 }
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Weight {
+    Light,
+    #[default]
+    Normal,
+    Bold,
+}
+
+/// Style of a run of text. Without a color, the backend's default is used.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TextStyle {
+    pub color: Option<Color>,
+    pub weight: Weight,
+    pub italic: bool,
+    pub underline: bool,
+}
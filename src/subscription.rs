@@ -0,0 +1,136 @@
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use types::CowString;
+
+/// Something that produces actions over time, polled by the `App`.
+pub trait Source<A> {
+    fn poll(&mut self, now: Instant) -> Vec<A>;
+}
+
+type StartFn<A> = dyn FnOnce(Instant) -> Box<dyn Source<A>>;
+
+/// A source the `App` should be listening to. Subscriptions are identified
+/// by their key: a source is started when its key first shows up and
+/// dropped when it is no longer returned.
+pub struct Subscription<A> {
+    key: CowString,
+    start: Box<StartFn<A>>,
+}
+
+impl<A: 'static> Subscription<A> {
+    pub fn new<K, F>(key: K, start: F) -> Self
+    where
+        K: Into<CowString>,
+        F: FnOnce(Instant) -> Box<dyn Source<A>> + 'static,
+    {
+        Subscription {
+            key: key.into(),
+            start: Box::new(start),
+        }
+    }
+
+    /// Produces an action every `period`. Ticks missed between two polls are
+    /// skipped rather than delivered all at once. With a zero period, every
+    /// poll produces one.
+    pub fn interval<K, F>(key: K, period: Duration, action: F) -> Self
+    where
+        K: Into<CowString>,
+        F: Fn() -> A + 'static,
+    {
+        Subscription::new(key, move |now| {
+            Box::new(Interval {
+                period,
+                next: now + period,
+                action,
+            }) as Box<dyn Source<A>>
+        })
+    }
+
+    /// Forwards every action received on `receiver`.
+    pub fn channel<K: Into<CowString>>(key: K, receiver: Arc<Mutex<Receiver<A>>>) -> Self {
+        Subscription::new(key, move |_| {
+            Box::new(Channel { receiver }) as Box<dyn Source<A>>
+        })
+    }
+}
+
+impl<A> Subscription<A> {
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+}
+
+struct Interval<F> {
+    period: Duration,
+    next: Instant,
+    action: F,
+}
+
+impl<A, F: Fn() -> A> Source<A> for Interval<F> {
+    fn poll(&mut self, now: Instant) -> Vec<A> {
+        if now < self.next {
+            return Vec::new();
+        }
+
+        // Moves past every tick up to `now` at once. A zero period stays due.
+        let period = self.period.as_nanos();
+        let ticks = match period {
+            0 => 0,
+            _ => (now - self.next).as_nanos() / period + 1,
+        };
+        self.next += Duration::from_nanos((period * ticks) as u64);
+
+        vec![(self.action)()]
+    }
+}
+
+struct Channel<A> {
+    receiver: Arc<Mutex<Receiver<A>>>,
+}
+
+impl<A> Source<A> for Channel<A> {
+    fn poll(&mut self, _now: Instant) -> Vec<A> {
+        self.receiver.lock().unwrap().try_iter().collect()
+    }
+}
+
+type SubscribeFn<S, A> = dyn Fn(&S) -> Vec<Subscription<A>>;
+
+pub(crate) struct Subscriptions<S, A> {
+    subscribe: Box<SubscribeFn<S, A>>,
+    active: Vec<(CowString, Box<dyn Source<A>>)>,
+}
+
+impl<S, A> Subscriptions<S, A> {
+    pub(crate) fn new(subscribe: impl Fn(&S) -> Vec<Subscription<A>> + 'static) -> Self {
+        Subscriptions {
+            subscribe: Box::new(subscribe),
+            active: Vec::new(),
+        }
+    }
+
+    pub(crate) fn restart(&mut self) {
+        self.active.clear();
+    }
+
+    pub(crate) fn update(&mut self, store: &S, now: Instant) {
+        let mut active = self.active.split_off(0);
+
+        for subscription in (self.subscribe)(store) {
+            let source = match active.iter().position(|(key, _)| *key == subscription.key) {
+                Some(index) => active.remove(index).1,
+                None => (subscription.start)(now),
+            };
+            self.active.push((subscription.key, source));
+        }
+    }
+
+    pub(crate) fn poll(&mut self, now: Instant) -> Vec<A> {
+        self.active
+            .iter_mut()
+            .flat_map(|(_, source)| source.poll(now))
+            .collect()
+    }
+}
@@ -1,10 +1,82 @@
 use std::sync::Arc;
+
+use render::{Size, DEFAULT_VIEWPORT};
+use state::{LocalHandle, LocalReducerFn, LocalStates, RenderContext, StateKey};
 use vnode::dynamic_node::DynamicNode;
+use vnode::static_node::StaticNode;
+use vnode::KeyValue;
+
+type RendererFn<S, M, A> = dyn Fn(&S, &Option<M>, &mut RenderContext) -> DynamicNode<S, M, A>;
+
+trait Memo<S, M, A> {
+    fn render(
+        &self,
+        store: &S,
+        message: &Option<M>,
+        context: &mut RenderContext,
+        render: &mut dyn FnMut(&mut RenderContext) -> StaticNode<A>,
+    ) -> StaticNode<A>;
+}
+
+// The last render of one instance of a memoized template, kept by the `App`
+// for the path it is at.
+struct Memoized<T, A> {
+    selected: T,
+    revision: usize,
+    viewport: Size,
+    node: StaticNode<A>,
+    visited: Vec<StateKey>,
+}
+
+struct Selector<F> {
+    select: F,
+}
 
-pub type RendererFn<S, M, A> = Fn(&S, &Option<M>) -> DynamicNode<S, M, A>;
+impl<S, M, A, T, F> Memo<S, M, A> for Selector<F>
+where
+    T: 'static + PartialEq,
+    F: Fn(&S, &Option<M>) -> T,
+    A: 'static,
+{
+    fn render(
+        &self,
+        store: &S,
+        message: &Option<M>,
+        context: &mut RenderContext,
+        render: &mut dyn FnMut(&mut RenderContext) -> StaticNode<A>,
+    ) -> StaticNode<A> {
+        let selected = (self.select)(store, message);
+
+        // Local state changes anywhere invalidate the memo as well.
+        if let Some(memo) = context.take_cached::<Memoized<T, A>>() {
+            if memo.selected == selected
+                && memo.revision == context.revision()
+                && memo.viewport == context.viewport()
+            {
+                let node = memo.node.clone();
+                context.revisit(&memo.visited);
+                context.cache(memo);
+                return node;
+            }
+        }
+
+        let start = context.visited();
+        let node = render(context);
+        let memo = Memoized {
+            selected,
+            revision: context.revision(),
+            viewport: context.viewport(),
+            node: node.clone(),
+            visited: context.visited_since(start),
+        };
+        context.cache(memo);
+        node
+    }
+}
 
 pub struct Template<S, M, A> {
     renderer: Box<RendererFn<S, M, A>>,
+    memo: Option<Box<dyn Memo<S, M, A>>>,
 }
 
 impl<S, M, A> Template<S, M, A> {
@@ -12,11 +84,97 @@ impl<S, M, A> Template<S, M, A> {
         renderer: impl 'static + Fn(&S, &Option<M>) -> DynamicNode<S, M, A>,
     ) -> Arc<Template<S, M, A>> {
         Arc::new(Template {
-            renderer: Box::new(renderer),
+            renderer: Box::new(move |store, message, _context| renderer(store, message)),
+            memo: None,
+        })
+    }
+
+    /// Like `new`, but the last render is reused for as long as `selector`
+    /// returns a value equal to the one it returned then. The `App` keeps
+    /// the last render of every place the template is used in.
+    pub fn memo<T, F>(
+        selector: F,
+        renderer: impl 'static + Fn(&S, &Option<M>) -> DynamicNode<S, M, A>,
+    ) -> Arc<Template<S, M, A>>
+    where
+        T: 'static + PartialEq,
+        F: 'static + Fn(&S, &Option<M>) -> T,
+        A: 'static,
+    {
+        Arc::new(Template {
+            renderer: Box::new(move |store, message, _context| renderer(store, message)),
+            memo: Some(Box::new(Selector { select: selector })),
+        })
+    }
+
+    /// A template with its own state `L`, created by `init` the first time
+    /// it is rendered and updated by `reducer` with local actions. The
+    /// renderer gets a `LocalHandle` to create them, e.g. for handlers set
+    /// with `on_local_click`.
+    ///
+    /// The state belongs to the template's `key`, or to its position in the
+    /// tree if it has none, and is dropped once it is no longer rendered.
+    pub fn stateful<L, LA>(
+        init: impl 'static + Fn() -> L,
+        reducer: impl 'static + Fn(L, LA) -> L,
+        renderer: impl 'static + Fn(&S, &Option<M>, &L, &LocalHandle<LA>) -> DynamicNode<S, M, A>,
+    ) -> Arc<Template<S, M, A>>
+    where
+        L: 'static,
+        LA: 'static,
+    {
+        let reducer: Arc<LocalReducerFn<L, LA>> = Arc::new(reducer);
+
+        Arc::new(Template {
+            renderer: Box::new(move |store, message, context| {
+                let handle = LocalHandle::new(context.state_key());
+                let state = context.state(&init, &reducer);
+                renderer(store, message, state, &handle)
+            }),
+            memo: None,
+        })
+    }
+
+    /// Like `new`, but `renderer` also gets the size of the viewport the
+    /// `App` is shown in, so the view can adapt to it.
+    pub fn sized(
+        renderer: impl 'static + Fn(&S, &Option<M>, Size) -> DynamicNode<S, M, A>,
+    ) -> Arc<Template<S, M, A>> {
+        Arc::new(Template {
+            renderer: Box::new(move |store, message, context| {
+                renderer(store, message, context.viewport())
+            }),
+            memo: None,
         })
     }
 
     pub fn render(&self, store: &S, message: &Option<M>) -> DynamicNode<S, M, A> {
-        (self.renderer)(store, message)
+        let mut states = LocalStates::new();
+        let mut context = RenderContext::new(&mut states, DEFAULT_VIEWPORT);
+        (self.renderer)(store, message, &mut context)
+    }
+
+    pub(crate) fn render_with(
+        &self,
+        store: &S,
+        message: &Option<M>,
+        key: Option<KeyValue>,
+        context: &mut RenderContext,
+    ) -> StaticNode<A> {
+        let mut render = |context: &mut RenderContext| {
+            let outer = context.set_key(key.clone());
+            let node = (self.renderer)(store, message, context);
+            context.set_key(outer);
+
+            context.enter(0);
+            let node = node.render_with(store, context);
+            context.leave();
+            node
+        };
+
+        match self.memo {
+            Some(ref memo) => memo.render(store, message, context, &mut render),
+            None => render(context),
+        }
     }
 }
@@ -1,3 +1,4 @@
+use std::marker::PhantomData;
 use std::sync::Arc;
 
 use super::dynamic_node::DynamicChildren;
@@ -5,195 +6,350 @@ use super::DynamicNode;
 use super::{
     AttrName, AttrValue, Attributes, Class, Classes, IdValue, KeyValue, Name, TextContent,
 };
-use super::{Item, Text};
+use super::{Item, Span, Text};
 
 use event::{ClickEvent, Handlers};
-use layout::ContainerLayout;
+use layout::{ContainerLayout, Direction, Media, Overflow, Override};
+use state::LocalAction;
 use template::Template;
 
-pub struct BuilderContainer<S, M, A> {
+//
+// # Builders
+//
+// Every kind of node has its own builder that only has the methods which
+// make sense for it. All of them turn into a `DynamicNode`, with `done` or
+// `into`, so any of them can be a child of a container.
+//
+
+/// Builds a text node. Text has no children:
+///
+/// ```compile_fail
+/// use cinnabar::TextBuilder;
+///
+/// TextBuilder::<(), (), ()>::new("OK").child(TextBuilder::new("more"));
+/// ```
+pub struct TextBuilder<S, M, A> {
+    text: Text<A>,
+    node: PhantomData<fn() -> (S, M)>,
+}
+
+/// Builds an item, a node with item properties and no children.
+pub struct ItemBuilder<S, M, A> {
+    item: Item<A>,
+    node: PhantomData<fn() -> (S, M)>,
+}
+
+/// Builds a container. Only templates are given a message:
+///
+/// ```compile_fail
+/// use cinnabar::ContainerBuilder;
+///
+/// ContainerBuilder::<(), (), ()>::new("panel").message(());
+/// ```
+pub struct ContainerBuilder<S, M, A> {
     item: Item<A>,
     children: DynamicChildren<S, M, A>,
     layout: ContainerLayout,
 }
 
-pub struct BuilderTemplate<S, M, A> {
+/// Places a template, with a message and a key. It has no item properties
+/// of its own, since it renders to whatever node its template returns:
+///
+/// ```compile_fail
+/// use cinnabar::{Template, TemplateBuilder, TextBuilder};
+///
+/// let template = Template::new(|_: &(), _: &Option<()>| {
+///     TextBuilder::<(), (), ()>::new("OK").done()
+/// });
+/// TemplateBuilder::new(template).id("ok");
+/// ```
+pub struct TemplateBuilder<S, M, A> {
     message: Option<M>,
+    key: Option<KeyValue>,
     template: Arc<Template<S, M, A>>,
 }
 
-pub enum Builder<S, M, A> {
-    Text(Text<A>),
-    Item(Item<A>),
-    Container(BuilderContainer<S, M, A>),
-    Template(BuilderTemplate<S, M, A>),
+fn item<A, T: Into<Name>>(name: T) -> Item<A> {
+    Item {
+        id: None,
+        name: name.into(),
+        key: None,
+        classes: Classes::new(),
+        attributes: Attributes::new(),
+        handlers: Handlers::new(),
+        layout: Default::default(),
+    }
 }
 
-impl<S, M, A> Builder<S, M, A> {
-    //
-    // # Constructors
-    //
+// Properties items and containers share, through their `item` field.
+macro_rules! item_properties {
+    () => {
+        pub fn id<T: Into<IdValue>>(mut self, id: T) -> Self {
+            self.item.id = Some(id.into());
+            self
+        }
 
-    pub fn text<T: Into<TextContent>>(content: T) -> Self {
-        Builder::Text(Text {
-            content: content.into(),
-            handlers: Handlers::new(),
-        })
-    }
+        pub fn key<T: Into<KeyValue>>(mut self, key: T) -> Self {
+            self.item.key = Some(key.into());
+            self
+        }
 
-    pub fn item<T: Into<Name>>(name: T) -> Self {
-        Builder::Item(Item {
-            id: None,
-            name: name.into(),
-            key: None,
-            classes: Classes::new(),
-            attributes: Attributes::new(),
-            handlers: Handlers::new(),
-            layout: Default::default(),
-        })
-    }
+        pub fn class<T: Into<Class>>(mut self, class: T) -> Self {
+            self.item.classes.insert(class.into());
+            self
+        }
 
-    pub fn container<T: Into<Name>>(name: T) -> Self {
-        let item = Item {
-            id: None,
-            name: name.into(),
-            key: None,
-            classes: Classes::new(),
-            attributes: Attributes::new(),
-            handlers: Handlers::new(),
-            layout: Default::default(),
-        };
+        pub fn classes<T: Into<Class>>(mut self, classes: T) -> Self {
+            for class in classes.into().as_ref().split_whitespace() {
+                self = self.class(class.to_string());
+            }
 
-        Builder::Container(BuilderContainer {
-            item,
-            children: DynamicChildren::new(),
-            layout: Default::default(),
-        })
+            self
+        }
+
+        pub fn attr<T: Into<AttrName>, P: Into<AttrValue>>(mut self, name: T, value: P) -> Self {
+            self.item.attributes.insert(name.into(), value.into());
+            self
+        }
+
+        /// Leaves the node out while the viewport matches `media`.
+        pub fn hidden_when(mut self, media: Media) -> Self {
+            self.item.layout.add_rule(media, Override::Hidden);
+            self
+        }
+
+        pub fn on_click<H>(mut self, handler: H) -> Self
+        where
+            H: Fn(ClickEvent) -> A + 'static,
+        {
+            self.item.handlers.click(handler);
+            self
+        }
+
+        pub fn on_local_click<H>(mut self, handler: H) -> Self
+        where
+            H: Fn(ClickEvent) -> LocalAction + 'static,
+        {
+            self.item.handlers.local_click(handler);
+            self
+        }
+    };
+}
+
+//
+// # Text
+//
+
+impl<S, M, A> TextBuilder<S, M, A> {
+    pub fn new<T: Into<TextContent>>(content: T) -> Self {
+        TextBuilder {
+            text: Text {
+                content: content.into(),
+                handlers: Handlers::new(),
+                spans: Vec::new(),
+                wrap: true,
+                overflow: Overflow::Clip,
+            },
+            node: PhantomData,
+        }
     }
 
-    //
-    // # Properties
-    //
+    /// Text made of `spans`, laid out and wrapped as one paragraph.
+    pub fn rich(spans: Vec<Span<A>>) -> Self {
+        let content: String = spans.iter().map(|span| span.content()).collect();
 
-    pub fn id<T: Into<IdValue>>(mut self, id: T) -> Self {
-        match self {
-            Builder::Container(BuilderContainer { ref mut item, .. }) => item.id = Some(id.into()),
-            Builder::Item(ref mut item) => item.id = Some(id.into()),
-            Builder::Text(_) => panic!("Text builder nodes do not have item properties"),
-            Builder::Template(_) => panic!("Template builder nodes do not have item properties"),
-        };
+        let mut builder = TextBuilder::new(content);
+        builder.text.spans = spans;
+        builder
+    }
 
+    /// Text wraps at line break opportunities by default. Without wrapping,
+    /// only newlines start a new line.
+    pub fn wrap(mut self, wrap: bool) -> Self {
+        self.text.wrap = wrap;
         self
     }
 
-    pub fn key<T: Into<KeyValue>>(mut self, key: T) -> Self {
-        match self {
-            Builder::Container(BuilderContainer { ref mut item, .. }) => {
-                item.key = Some(key.into())
-            }
-            Builder::Item(ref mut item) => item.key = Some(key.into()),
-            Builder::Text(_) => panic!("Text builder nodes do not have item properties"),
-            Builder::Template(_) => panic!("Template builder nodes do not have item properties"),
-        };
-
+    /// How lines that do not fit are cut. Defaults to `Overflow::Clip`.
+    /// Only applies to text that does not wrap, since wrapped lines always
+    /// fit, so e.g. `Overflow::Scroll` needs `wrap(false)`.
+    pub fn overflow(mut self, overflow: Overflow) -> Self {
+        self.text.overflow = overflow;
         self
     }
 
-    pub fn class<T: Into<Class>>(mut self, class: T) -> Self {
-        match self {
-            Builder::Container(BuilderContainer { ref mut item, .. }) => {
-                item.classes.insert(class.into())
-            }
-            Builder::Item(ref mut item) => item.classes.insert(class.into()),
-            Builder::Text(_) => panic!("Text builder nodes do not have item properties"),
-            Builder::Template(_) => panic!("Template builder nodes do not have item properties"),
-        };
+    pub fn on_click<H>(mut self, handler: H) -> Self
+    where
+        H: Fn(ClickEvent) -> A + 'static,
+    {
+        self.text.handlers.click(handler);
+        self
+    }
 
+    pub fn on_local_click<H>(mut self, handler: H) -> Self
+    where
+        H: Fn(ClickEvent) -> LocalAction + 'static,
+    {
+        self.text.handlers.local_click(handler);
         self
     }
 
-    pub fn classes<T: Into<Class>>(mut self, classes: T) -> Self {
-        for class in classes.into().as_ref().split_whitespace() {
-            self = self.class(class.to_string());
-        }
+    pub fn done(self) -> DynamicNode<S, M, A> {
+        DynamicNode::new_text(self.text)
+    }
+}
 
-        self
+//
+// # Item
+//
+
+impl<S, M, A> ItemBuilder<S, M, A> {
+    pub fn new<T: Into<Name>>(name: T) -> Self {
+        ItemBuilder {
+            item: item(name),
+            node: PhantomData,
+        }
     }
 
-    pub fn attr<T: Into<AttrName>, P: Into<AttrValue>>(mut self, name: T, value: P) -> Self {
-        match self {
-            Builder::Container(BuilderContainer { ref mut item, .. }) => {
-                item.attributes.insert(name.into(), value.into());
-            }
-            Builder::Item(ref mut item) => {
-                item.attributes.insert(name.into(), value.into());
-            }
-            Builder::Text(_) => panic!("Text builder nodes do not have item properties"),
-            Builder::Template(_) => panic!("Template builder nodes do not have item properties"),
-        };
+    item_properties!();
 
-        self
+    pub fn done(self) -> DynamicNode<S, M, A> {
+        DynamicNode::new_item(self.item)
     }
+}
 
-    pub fn message(mut self, message: M) -> Self {
-        match self {
-            Builder::Template(ref mut template) => template.message = Some(message),
-            _ => panic!("Only Template can have message."),
+//
+// # Container
+//
+
+impl<S, M, A> ContainerBuilder<S, M, A> {
+    pub fn new<T: Into<Name>>(name: T) -> Self {
+        ContainerBuilder {
+            item: item(name),
+            children: DynamicChildren::new(),
+            layout: Default::default(),
         }
+    }
+
+    item_properties!();
 
+    /// Applies `change` only while the viewport matches `media`. Items have
+    /// no layout of their own to change, so they only have `hidden_when`:
+    ///
+    /// ```compile_fail
+    /// use cinnabar::{ItemBuilder, Media, Override};
+    ///
+    /// ItemBuilder::<(), (), ()>::new("icon").when(Media::new(), Override::Hidden);
+    /// ```
+    pub fn when(mut self, media: Media, change: Override) -> Self {
+        self.item.layout.add_rule(media, change);
         self
     }
 
-    pub fn on_click<H>(mut self, handler: H) -> Self
-    where
-        H: Fn(ClickEvent) -> A + 'static,
-    {
-        match self {
-            Builder::Container(ref mut container) => container.item.handlers.click(handler),
-            Builder::Item(ref mut item) => item.handlers.click(handler),
-            Builder::Text(ref mut text) => text.handlers.click(handler),
-            _ => panic!("Template nodes do not have handlers"),
-        }
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.layout.set_direction(direction);
+        self
+    }
 
+    pub fn child<T: Into<DynamicNode<S, M, A>>>(mut self, child: T) -> Self {
+        self.children.push(child.into());
         self
     }
 
-    pub fn child<T: Into<Builder<S, M, A>>>(mut self, child: T) -> Self {
-        match self {
-            Builder::Container(ref mut container) => {
-                container.children.push(child.into().done());
-            }
-            _ => panic!("Only Container can have children."),
+    pub fn done(self) -> DynamicNode<S, M, A> {
+        DynamicNode::new_container(self.item, self.children, self.layout)
+    }
+}
+
+//
+// # Template
+//
+
+impl<S, M, A> TemplateBuilder<S, M, A> {
+    pub fn new(template: Arc<Template<S, M, A>>) -> Self {
+        TemplateBuilder {
+            template,
+            message: None,
+            key: None,
         }
+    }
 
+    pub fn key<T: Into<KeyValue>>(mut self, key: T) -> Self {
+        self.key = Some(key.into());
         self
     }
 
-    //
-    // # Converters
-    //
+    pub fn message(mut self, message: M) -> Self {
+        self.message = Some(message);
+        self
+    }
 
     pub fn done(self) -> DynamicNode<S, M, A> {
-        match self {
-            Builder::Text(text) => DynamicNode::new_text(text),
-            Builder::Item(item) => DynamicNode::new_item(item),
-            Builder::Container(BuilderContainer {
-                item,
-                children,
-                layout,
-            }) => DynamicNode::new_container(item, children, layout),
-            Builder::Template(BuilderTemplate { template, message }) => {
-                DynamicNode::new_template(template, message)
-            }
-        }
+        DynamicNode::new_template(self.template, self.message, self.key)
     }
 }
 
-impl<S, M, A> From<Arc<Template<S, M, A>>> for Builder<S, M, A> {
+impl<S, M, A> From<Arc<Template<S, M, A>>> for TemplateBuilder<S, M, A> {
     fn from(template: Arc<Template<S, M, A>>) -> Self {
-        Builder::Template(BuilderTemplate {
-            template,
-            message: None,
-        })
+        TemplateBuilder::new(template)
+    }
+}
+
+//
+// # Builder
+//
+// One builder used to make every kind of node, panicking on methods that
+// did not apply. It only remains to construct the typed builders.
+//
+
+#[deprecated(note = "Use TextBuilder, ItemBuilder, ContainerBuilder or TemplateBuilder")]
+pub struct Builder<S, M, A> {
+    node: PhantomData<fn(S, M) -> A>,
+}
+
+#[allow(deprecated)]
+impl<S, M, A> Builder<S, M, A> {
+    pub fn text<T: Into<TextContent>>(content: T) -> TextBuilder<S, M, A> {
+        TextBuilder::new(content)
+    }
+
+    pub fn rich(spans: Vec<Span<A>>) -> TextBuilder<S, M, A> {
+        TextBuilder::rich(spans)
+    }
+
+    pub fn item<T: Into<Name>>(name: T) -> ItemBuilder<S, M, A> {
+        ItemBuilder::new(name)
+    }
+
+    pub fn container<T: Into<Name>>(name: T) -> ContainerBuilder<S, M, A> {
+        ContainerBuilder::new(name)
+    }
+}
+
+//
+// # Converters
+//
+
+impl<S, M, A> From<TextBuilder<S, M, A>> for DynamicNode<S, M, A> {
+    fn from(builder: TextBuilder<S, M, A>) -> Self {
+        builder.done()
+    }
+}
+
+impl<S, M, A> From<ItemBuilder<S, M, A>> for DynamicNode<S, M, A> {
+    fn from(builder: ItemBuilder<S, M, A>) -> Self {
+        builder.done()
+    }
+}
+
+impl<S, M, A> From<ContainerBuilder<S, M, A>> for DynamicNode<S, M, A> {
+    fn from(builder: ContainerBuilder<S, M, A>) -> Self {
+        builder.done()
+    }
+}
+
+impl<S, M, A> From<TemplateBuilder<S, M, A>> for DynamicNode<S, M, A> {
+    fn from(builder: TemplateBuilder<S, M, A>) -> Self {
+        builder.done()
     }
 }
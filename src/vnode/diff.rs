@@ -0,0 +1,529 @@
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
+use std::sync::Arc;
+
+use super::static_node::{StaticChildren, StaticItem};
+use super::{AttrName, AttrValue, Item, KeyValue, StaticNode, Text, TextContent};
+
+use event::Handlers;
+
+pub type Path = Vec<usize>;
+
+/// A single update that turns one `StaticNode` tree into another.
+///
+/// Patches are meant to be applied in order. Paths are child indices from
+/// the root and always refer to the tree as it is after the preceding
+/// patches were applied. `Move` removes the child at `from` and then inserts
+/// it at `to`. Class changes are reported as a `class` attribute.
+pub enum Patch<A> {
+    Insert {
+        parent: Path,
+        index: usize,
+        node: StaticNode<A>,
+    },
+    Remove {
+        parent: Path,
+        index: usize,
+    },
+    Move {
+        parent: Path,
+        from: usize,
+        to: usize,
+    },
+    Replace {
+        path: Path,
+        node: StaticNode<A>,
+    },
+    SetAttribute {
+        path: Path,
+        name: AttrName,
+        value: Option<AttrValue>,
+    },
+    SetText {
+        path: Path,
+        content: TextContent,
+    },
+    /// The node's handlers, which capture different values than before.
+    SetHandlers {
+        path: Path,
+        handlers: Handlers<A>,
+    },
+}
+
+impl<A> fmt::Debug for Patch<A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Patch::Insert {
+                parent,
+                index,
+                node,
+            } => write!(f, "Insert {:?}[{}] {}", parent, index, node),
+            Patch::Remove { parent, index } => write!(f, "Remove {:?}[{}]", parent, index),
+            Patch::Move { parent, from, to } => {
+                write!(f, "Move {:?}[{}] -> [{}]", parent, from, to)
+            }
+            Patch::Replace { path, node } => write!(f, "Replace {:?} {}", path, node),
+            Patch::SetAttribute {
+                path,
+                name,
+                value: Some(value),
+            } => write!(f, "SetAttribute {:?} {}=\"{}\"", path, name, value),
+            Patch::SetAttribute {
+                path,
+                name,
+                value: None,
+            } => write!(f, "RemoveAttribute {:?} {}", path, name),
+            Patch::SetText { path, content } => write!(f, "SetText {:?} {}", path, content),
+            Patch::SetHandlers { path, .. } => write!(f, "SetHandlers {:?}", path),
+        }
+    }
+}
+
+/// Computes the patches that turn `old` into `new`.
+///
+/// Children are matched by `key` where they have one and by position among
+/// unkeyed siblings otherwise. Reordered keyed children are moved, keeping
+/// the longest run that is already in order in place. Subtrees shared
+/// between both trees are skipped. Nodes whose layout changed are replaced.
+///
+/// Adding or removing a handler replaces the node. Handlers that are kept
+/// but are different closures are set again, since they may capture other
+/// values, e.g. the row they belong to. Rich text whose spans have different
+/// closures is replaced.
+pub fn diff<A>(old: &StaticNode<A>, new: &StaticNode<A>) -> Result<Vec<Patch<A>>, DuplicateKey> {
+    check_keys(&mut Vec::new(), new)?;
+
+    let mut patches = Vec::new();
+    diff_node(&mut Vec::new(), old, new, &mut patches)?;
+    Ok(patches)
+}
+
+fn diff_node<A>(
+    path: &mut Path,
+    old: &StaticNode<A>,
+    new: &StaticNode<A>,
+    patches: &mut Vec<Patch<A>>,
+) -> Result<(), DuplicateKey> {
+    if old.ptr_eq(new) {
+        return Ok(());
+    }
+
+    match (old, new) {
+        (StaticNode::Text(old_text), StaticNode::Text(new_text)) => {
+            if old_text.wrap != new_text.wrap
+                || old_text.overflow != new_text.overflow
+                || !same_handlers(&old_text.handlers, &new_text.handlers)
+                || !same_spans(old_text, new_text)
+            {
+                replace(path, new, patches);
+            } else {
+                if old_text.content != new_text.content {
+                    patches.push(Patch::SetText {
+                        path: path.clone(),
+                        content: new_text.content.clone(),
+                    });
+                }
+                diff_handlers(path, &old_text.handlers, &new_text.handlers, patches);
+            }
+        }
+        (StaticNode::Item(old_item), StaticNode::Item(new_item)) => {
+            if same_identity(old_item, new_item) && same_behavior(old_item, new_item) {
+                diff_item(path, old_item, new_item, patches);
+            } else {
+                replace(path, new, patches);
+            }
+        }
+        (StaticNode::Container(old_container), StaticNode::Container(new_container)) => {
+            let (old_item, new_item) = (old_container.item(), new_container.item());
+            let same_layout = Arc::ptr_eq(old_container.layout(), new_container.layout())
+                || old_container.layout() == new_container.layout();
+
+            if same_identity(old_item, new_item) && same_behavior(old_item, new_item) && same_layout
+            {
+                diff_item(path, old_item, new_item, patches);
+                diff_children(
+                    path,
+                    old_container.children(),
+                    new_container.children(),
+                    patches,
+                )?;
+            } else {
+                replace(path, new, patches);
+            }
+        }
+        _ => replace(path, new, patches),
+    }
+
+    Ok(())
+}
+
+fn replace<A>(path: &Path, node: &StaticNode<A>, patches: &mut Vec<Patch<A>>) {
+    patches.push(Patch::Replace {
+        path: path.clone(),
+        node: node.clone(),
+    });
+}
+
+fn same_identity<A>(old: &StaticItem<A>, new: &StaticItem<A>) -> bool {
+    old.name == new.name && old.id == new.id && old.key == new.key
+}
+
+/// Whether an item lays out and handles events the same way, as far as a
+/// diff can tell.
+fn same_behavior<A>(old: &StaticItem<A>, new: &StaticItem<A>) -> bool {
+    old.layout == new.layout && same_handlers(&old.handlers, &new.handlers)
+}
+
+fn same_handlers<A>(old: &Handlers<A>, new: &Handlers<A>) -> bool {
+    old.click.is_some() == new.click.is_some()
+        && old.local_click.is_some() == new.local_click.is_some()
+}
+
+fn same_closures<A>(old: &Handlers<A>, new: &Handlers<A>) -> bool {
+    fn same<T: ?Sized>(old: &Option<Arc<T>>, new: &Option<Arc<T>>) -> bool {
+        match (old, new) {
+            (Some(old), Some(new)) => Arc::ptr_eq(old, new),
+            (old, new) => old.is_none() && new.is_none(),
+        }
+    }
+
+    same(&old.click, &new.click) && same(&old.local_click, &new.local_click)
+}
+
+fn diff_handlers<A>(
+    path: &Path,
+    old: &Handlers<A>,
+    new: &Handlers<A>,
+    patches: &mut Vec<Patch<A>>,
+) {
+    if !same_closures(old, new) {
+        patches.push(Patch::SetHandlers {
+            path: path.clone(),
+            handlers: new.clone(),
+        });
+    }
+}
+
+fn same_spans<A>(old: &Text<A>, new: &Text<A>) -> bool {
+    old.spans.len() == new.spans.len()
+        && old.spans.iter().zip(new.spans.iter()).all(|(old, new)| {
+            old.content == new.content
+                && old.style == new.style
+                && old.classes == new.classes
+                && same_closures(&old.handlers, &new.handlers)
+        })
+}
+
+fn class_list<A>(item: &Item<A>) -> Option<AttrValue> {
+    if item.classes.is_empty() {
+        return None;
+    }
+
+    let mut classes: Vec<&str> = item.classes.iter().map(|class| class.as_ref()).collect();
+    classes.sort();
+    Some(classes.join(" ").into())
+}
+
+fn diff_item<A>(
+    path: &Path,
+    old: &StaticItem<A>,
+    new: &StaticItem<A>,
+    patches: &mut Vec<Patch<A>>,
+) {
+    if old.classes != new.classes {
+        patches.push(Patch::SetAttribute {
+            path: path.clone(),
+            name: "class".into(),
+            value: class_list(new),
+        });
+    }
+
+    let mut names: Vec<&AttrName> = old.attributes.keys().chain(new.attributes.keys()).collect();
+    names.sort();
+    names.dedup();
+
+    for name in names {
+        let value = new.attributes.get(name);
+        if old.attributes.get(name) != value {
+            patches.push(Patch::SetAttribute {
+                path: path.clone(),
+                name: name.clone(),
+                value: value.cloned(),
+            });
+        }
+    }
+
+    diff_handlers(path, &old.handlers, &new.handlers, patches);
+}
+
+fn node_key<A>(node: &StaticNode<A>) -> Option<&str> {
+    match node {
+        StaticNode::Item(item) => item.key(),
+        StaticNode::Container(container) => container.item().key(),
+        StaticNode::Text(_) => None,
+    }
+}
+
+/// Sibling nodes that share a `key`, found while diffing.
+#[derive(Debug, PartialEq)]
+pub struct DuplicateKey {
+    pub parent: Path,
+    pub key: KeyValue,
+}
+
+impl fmt::Display for DuplicateKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Duplicate key \"{}\" among children of {:?}",
+            self.key, self.parent
+        )
+    }
+}
+
+impl error::Error for DuplicateKey {}
+
+fn keyed_indices<'a, A>(
+    path: &Path,
+    children: &'a StaticChildren<A>,
+) -> Result<HashMap<&'a str, usize>, DuplicateKey> {
+    let mut keys = HashMap::new();
+
+    for (index, child) in children.iter().enumerate() {
+        if let Some(key) = node_key(child) {
+            if keys.insert(key, index).is_some() {
+                return Err(DuplicateKey {
+                    parent: path.clone(),
+                    key: key.to_string().into(),
+                });
+            }
+        }
+    }
+
+    Ok(keys)
+}
+
+fn check_keys<A>(path: &mut Path, node: &StaticNode<A>) -> Result<(), DuplicateKey> {
+    if let StaticNode::Container(container) = node {
+        keyed_indices(path, container.children())?;
+
+        for (index, child) in container.children().iter().enumerate() {
+            path.push(index);
+            check_keys(path, child)?;
+            path.pop();
+        }
+    }
+
+    Ok(())
+}
+
+// For every new child, the index of the old child it is matched with.
+fn match_children<A>(
+    path: &Path,
+    old: &StaticChildren<A>,
+    new: &StaticChildren<A>,
+) -> Result<Vec<Option<usize>>, DuplicateKey> {
+    let old_keys = keyed_indices(path, old)?;
+
+    let mut unkeyed = old
+        .iter()
+        .enumerate()
+        .filter(|(_, child)| node_key(child).is_none())
+        .map(|(index, _)| index);
+
+    Ok(new
+        .iter()
+        .map(|child| match node_key(child) {
+            Some(key) => old_keys.get(key).cloned(),
+            None => unkeyed.next(),
+        })
+        .collect())
+}
+
+// Marks the new children that belong to a longest run whose old indices are
+// increasing. Those keep their relative order and never have to move.
+fn stable_children(matches: &[Option<usize>]) -> Vec<bool> {
+    // tails[length - 1] is the new index ending the best run of that length.
+    let mut tails: Vec<usize> = Vec::new();
+    let mut previous: Vec<Option<usize>> = vec![None; matches.len()];
+
+    for (index, found) in matches.iter().enumerate() {
+        let old_index = match found {
+            Some(old_index) => *old_index,
+            None => continue,
+        };
+
+        let length = match tails.binary_search_by(|tail| matches[*tail].cmp(&Some(old_index))) {
+            Ok(length) | Err(length) => length,
+        };
+
+        if length > 0 {
+            previous[index] = Some(tails[length - 1]);
+        }
+
+        if length == tails.len() {
+            tails.push(index);
+        } else {
+            tails[length] = index;
+        }
+    }
+
+    let mut stable = vec![false; matches.len()];
+    let mut cursor = tails.last().cloned();
+    while let Some(index) = cursor {
+        stable[index] = true;
+        cursor = previous[index];
+    }
+
+    stable
+}
+
+fn diff_children<A>(
+    path: &mut Path,
+    old: &StaticChildren<A>,
+    new: &StaticChildren<A>,
+    patches: &mut Vec<Patch<A>>,
+) -> Result<(), DuplicateKey> {
+    let matches = match_children(path, old, new)?;
+    let stable = stable_children(&matches);
+
+    let mut new_indices = vec![None; old.len()];
+    for (index, found) in matches.iter().enumerate() {
+        if let Some(old_index) = found {
+            new_indices[*old_index] = Some(index);
+        }
+    }
+
+    // Remove unmatched old children, back to front so indices stay valid.
+    // `current` tracks the new index of every child as patches are applied.
+    let mut current: Vec<usize> = Vec::new();
+    for (index, new_index) in new_indices.iter().enumerate().rev() {
+        match new_index {
+            Some(new_index) => current.insert(0, *new_index),
+            None => patches.push(Patch::Remove {
+                parent: path.clone(),
+                index,
+            }),
+        }
+    }
+
+    // Place children back to front, each one right before its next sibling.
+    for index in (0..new.len()).rev() {
+        if stable[index] {
+            continue;
+        }
+
+        let anchor = |current: &Vec<usize>| {
+            current
+                .iter()
+                .position(|c| *c == index + 1)
+                .unwrap_or(current.len())
+        };
+
+        if matches[index].is_some() {
+            let from = current.iter().position(|c| *c == index).unwrap();
+            current.remove(from);
+            let to = anchor(&current);
+            current.insert(to, index);
+            patches.push(Patch::Move {
+                parent: path.clone(),
+                from,
+                to,
+            });
+        } else {
+            let to = anchor(&current);
+            current.insert(to, index);
+            patches.push(Patch::Insert {
+                parent: path.clone(),
+                index: to,
+                node: new[index].clone(),
+            });
+        }
+    }
+
+    for (index, found) in matches.iter().enumerate() {
+        if let Some(old_index) = found {
+            path.push(index);
+            diff_node(path, &old[*old_index], &new[index], patches)?;
+            path.pop();
+        }
+    }
+
+    Ok(())
+}
+
+/// Applies `patches` produced by `diff` to `node`.
+pub fn apply<A>(node: &mut StaticNode<A>, patches: Vec<Patch<A>>) {
+    for patch in patches {
+        match patch {
+            Patch::Insert {
+                parent,
+                index,
+                node: child,
+            } => children_at(node, &parent).insert(index, child),
+            Patch::Remove { parent, index } => {
+                children_at(node, &parent).remove(index);
+            }
+            Patch::Move { parent, from, to } => {
+                let children = children_at(node, &parent);
+                let child = children.remove(from);
+                children.insert(to, child);
+            }
+            Patch::Replace { path, node: new } => *node_at(node, &path) = new,
+            Patch::SetAttribute { path, name, value } => {
+                let item = match node_at(node, &path) {
+                    StaticNode::Item(item) => item,
+                    StaticNode::Container(container) => Arc::make_mut(container).item_mut(),
+                    StaticNode::Text(_) => panic!("Text nodes do not have attributes"),
+                };
+                let item = Arc::make_mut(item);
+                match (name.as_ref(), value) {
+                    ("class", value) => {
+                        item.classes = value
+                            .iter()
+                            .flat_map(|classes| classes.split_whitespace())
+                            .map(|class| class.to_string().into())
+                            .collect();
+                    }
+                    (_, Some(value)) => {
+                        item.attributes.insert(name, value);
+                    }
+                    (_, None) => {
+                        item.attributes.remove(&name);
+                    }
+                }
+            }
+            Patch::SetText { path, content } => match node_at(node, &path) {
+                StaticNode::Text(text) => {
+                    *text = Arc::new(Text {
+                        content,
+                        ..(**text).clone()
+                    })
+                }
+                _ => panic!("Only Text nodes have text content"),
+            },
+            Patch::SetHandlers { path, handlers } => match node_at(node, &path) {
+                StaticNode::Text(text) => Arc::make_mut(text).handlers = handlers,
+                StaticNode::Item(item) => Arc::make_mut(item).handlers = handlers,
+                StaticNode::Container(container) => {
+                    Arc::make_mut(Arc::make_mut(container).item_mut()).handlers = handlers
+                }
+            },
+        }
+    }
+}
+
+fn node_at<'a, A>(node: &'a mut StaticNode<A>, path: &[usize]) -> &'a mut StaticNode<A> {
+    match path.split_first() {
+        Some((index, rest)) => node_at(&mut children_at(node, &[])[*index], rest),
+        None => node,
+    }
+}
+
+fn children_at<'a, A>(node: &'a mut StaticNode<A>, path: &[usize]) -> &'a mut StaticChildren<A> {
+    match node_at(node, path) {
+        StaticNode::Container(container) => Arc::make_mut(container).children_mut(),
+        _ => panic!("Only Container nodes have children"),
+    }
+}
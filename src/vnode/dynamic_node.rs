@@ -1,7 +1,9 @@
 use std::sync::Arc;
 
-use super::{Item, Text};
+use super::{Item, KeyValue, Text};
 use layout::ContainerLayout;
+use render::DEFAULT_VIEWPORT;
+use state::{LocalStates, RenderContext};
 use template::Template;
 
 use super::static_node::StaticNode;
@@ -15,18 +17,57 @@ pub struct DynamicContainer<S, M, A> {
     item: DynamicItem<A>,
     children: DynamicChildren<S, M, A>,
     layout: Arc<ContainerLayout>,
+    // Rendered once up front when no Template is nested inside.
+    cache: Option<StaticNode<A>>,
 }
 
 pub struct DynamicTemplate<S, M, A> {
     message: Option<M>,
+    key: Option<KeyValue>,
     template: Arc<Template<S, M, A>>,
 }
 
+trait Embed<S, A> {
+    fn render_with(&self, store: &S, context: &mut RenderContext) -> StaticNode<A>;
+}
+
+struct Embedded<S2, M2, A2, A, L> {
+    node: DynamicNode<S2, M2, A2>,
+    lens: L,
+    map: Arc<dyn Fn(A2) -> A>,
+}
+
+impl<S, S2, M2, A2, A, L> Embed<S, A> for Embedded<S2, M2, A2, A, L>
+where
+    L: Fn(&S) -> &S2,
+    A2: 'static,
+    A: 'static,
+{
+    /// Parts of the embedded tree that are shared with the last render stay
+    /// shared once mapped.
+    fn render_with(&self, store: &S, context: &mut RenderContext) -> StaticNode<A> {
+        let node = self.node.render_with((self.lens)(store), context);
+        let mapped = match context.take_cached::<(StaticNode<A2>, StaticNode<A>)>() {
+            Some((source, last)) => node.map_reusing(&self.map, &source, &last),
+            None => node.map(&self.map),
+        };
+
+        context.cache((node, mapped.clone()));
+        mapped
+    }
+}
+
+pub struct DynamicMap<S, A> {
+    node: Box<dyn Embed<S, A>>,
+    cache: Option<StaticNode<A>>,
+}
+
 pub enum DynamicNode<S, M, A> {
     Text(DynamicText<A>),
     Item(DynamicItem<A>),
     Container(DynamicContainer<S, M, A>),
     Template(DynamicTemplate<S, M, A>),
+    Map(DynamicMap<S, A>),
 }
 
 impl<S, M, A> DynamicNode<S, M, A> {
@@ -43,18 +84,81 @@ impl<S, M, A> DynamicNode<S, M, A> {
         children: DynamicChildren<S, M, A>,
         layout: ContainerLayout,
     ) -> Self {
+        let item = Arc::new(item);
+        let layout = Arc::new(layout);
+
+        let cache = children
+            .iter()
+            .map(|child| child.render_static())
+            .collect::<Option<_>>()
+            .map(|children| StaticNode::new_container(item.clone(), children, layout.clone()));
+
         DynamicNode::Container(DynamicContainer {
-            item: Arc::new(item),
+            item,
             children,
-            layout: Arc::new(layout),
+            layout,
+            cache,
+        })
+    }
+
+    pub fn new_template(
+        template: Arc<Template<S, M, A>>,
+        message: Option<M>,
+        key: Option<KeyValue>,
+    ) -> Self {
+        DynamicNode::Template(DynamicTemplate {
+            template,
+            message,
+            key,
+        })
+    }
+
+    /// Embeds a node written against another store and action type, like a
+    /// widget from a library. `lens` selects the part of the store it sees
+    /// and `map` turns the actions its handlers produce into ours.
+    pub fn map<S2, M2, A2, T, L, F>(node: T, lens: L, map: F) -> Self
+    where
+        T: Into<DynamicNode<S2, M2, A2>>,
+        L: 'static + Fn(&S) -> &S2,
+        F: 'static + Fn(A2) -> A,
+        S2: 'static,
+        M2: 'static,
+        A2: 'static,
+        A: 'static,
+    {
+        let node = node.into();
+        let map: Arc<dyn Fn(A2) -> A> = Arc::new(map);
+        let cache = node
+            .render_static()
+            .map(|static_node| static_node.map(&map));
+
+        DynamicNode::Map(DynamicMap {
+            node: Box::new(Embedded { node, lens, map }),
+            cache,
         })
     }
 
-    pub fn new_template(template: Arc<Template<S, M, A>>, message: Option<M>) -> Self {
-        DynamicNode::Template(DynamicTemplate { template, message })
+    fn render_static(&self) -> Option<StaticNode<A>> {
+        match self {
+            DynamicNode::Text(node) => Some(StaticNode::new_text(node.clone())),
+            DynamicNode::Item(node) => Some(StaticNode::new_item(node.clone())),
+            DynamicNode::Container(DynamicContainer { cache, .. }) => cache.clone(),
+            DynamicNode::Template(_) => None,
+            DynamicNode::Map(DynamicMap { cache, .. }) => cache.clone(),
+        }
     }
 
     pub fn render(&self, store: &S) -> StaticNode<A> {
+        let mut states = LocalStates::new();
+        let mut context = RenderContext::new(&mut states, DEFAULT_VIEWPORT);
+        self.render_with(store, &mut context)
+    }
+
+    pub(crate) fn render_with(&self, store: &S, context: &mut RenderContext) -> StaticNode<A> {
+        if let Some(node) = self.render_static() {
+            return node;
+        }
+
         match self {
             DynamicNode::Text(node) => StaticNode::new_text(node.clone()),
             DynamicNode::Item(node) => StaticNode::new_item(node.clone()),
@@ -62,14 +166,27 @@ impl<S, M, A> DynamicNode<S, M, A> {
                 item,
                 children,
                 layout,
+                ..
             }) => StaticNode::new_container(
                 item.clone(),
-                children.iter().map(|child| child.render(store)).collect(),
+                children
+                    .iter()
+                    .enumerate()
+                    .map(|(index, child)| {
+                        context.enter(index);
+                        let node = child.render_with(store, context);
+                        context.leave();
+                        node
+                    })
+                    .collect(),
                 layout.clone(),
             ),
-            DynamicNode::Template(DynamicTemplate { template, message }) => {
-                template.render(store, message).render(store)
-            }
+            DynamicNode::Template(DynamicTemplate {
+                template,
+                message,
+                key,
+            }) => template.render_with(store, message, key.clone(), context),
+            DynamicNode::Map(DynamicMap { node, .. }) => node.render_with(store, context),
         }
     }
 }
@@ -79,6 +196,7 @@ impl<S, M, A> From<Arc<Template<S, M, A>>> for DynamicNode<S, M, A> {
         DynamicNode::Template(DynamicTemplate {
             template,
             message: None,
+            key: None,
         })
     }
 }
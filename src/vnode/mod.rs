@@ -1,9 +1,14 @@
-use event::Handlers;
-use layout::ItemLayout;
+use event::{ClickEvent, Handlers};
+use layout::{ItemLayout, Overflow};
+use state::LocalAction;
 use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::sync::Arc;
+use style::{Color, TextStyle, Weight};
 use types::CowString;
 
 pub mod builder;
+pub mod diff;
 pub mod dynamic_node;
 mod format;
 pub mod static_node;
@@ -23,9 +28,139 @@ pub type AttrName = CowString;
 pub type AttrValue = CowString;
 pub type Attributes = HashMap<AttrName, AttrValue>;
 
+/// A styled part of rich text, which can have its own click handler and
+/// classes.
+pub struct Span<A> {
+    content: TextContent,
+    style: TextStyle,
+    classes: Classes,
+    handlers: Handlers<A>,
+}
+
+impl<A> Clone for Span<A> {
+    fn clone(&self) -> Self {
+        Span {
+            content: self.content.clone(),
+            style: self.style,
+            classes: self.classes.clone(),
+            handlers: self.handlers.clone(),
+        }
+    }
+}
+
+impl<A> Span<A> {
+    pub fn new<T: Into<TextContent>>(content: T) -> Self {
+        Span {
+            content: content.into(),
+            style: TextStyle::default(),
+            classes: Classes::new(),
+            handlers: Handlers::new(),
+        }
+    }
+
+    pub fn class<T: Into<Class>>(mut self, class: T) -> Self {
+        self.classes.insert(class.into());
+        self
+    }
+
+    pub fn color(mut self, color: Color) -> Self {
+        self.style.color = Some(color);
+        self
+    }
+
+    pub fn weight(mut self, weight: Weight) -> Self {
+        self.style.weight = weight;
+        self
+    }
+
+    pub fn bold(self) -> Self {
+        self.weight(Weight::Bold)
+    }
+
+    pub fn italic(mut self) -> Self {
+        self.style.italic = true;
+        self
+    }
+
+    pub fn underline(mut self) -> Self {
+        self.style.underline = true;
+        self
+    }
+
+    pub fn with_style(mut self, style: TextStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn on_click<H>(mut self, handler: H) -> Self
+    where
+        H: Fn(ClickEvent) -> A + 'static,
+    {
+        self.handlers.click(handler);
+        self
+    }
+
+    pub fn on_local_click<H>(mut self, handler: H) -> Self
+    where
+        H: Fn(ClickEvent) -> LocalAction + 'static,
+    {
+        self.handlers.local_click(handler);
+        self
+    }
+
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    pub fn style(&self) -> TextStyle {
+        self.style
+    }
+
+    pub fn classes(&self) -> &Classes {
+        &self.classes
+    }
+
+    pub fn has_class(&self, class: &str) -> bool {
+        self.classes.contains(class)
+    }
+
+    pub fn handlers(&self) -> &Handlers<A> {
+        &self.handlers
+    }
+
+    fn map<B>(&self, f: &Arc<dyn Fn(A) -> B>) -> Span<B>
+    where
+        A: 'static,
+        B: 'static,
+    {
+        Span {
+            content: self.content.clone(),
+            style: self.style,
+            classes: self.classes.clone(),
+            handlers: self.handlers.map(f),
+        }
+    }
+}
+
 pub struct Text<A> {
     content: TextContent,
     handlers: Handlers<A>,
+    // Empty for plain text, otherwise they make up `content` in order.
+    spans: Vec<Span<A>>,
+    wrap: bool,
+    overflow: Overflow,
+}
+
+impl<A> Clone for Text<A> {
+    fn clone(&self) -> Self {
+        Text {
+            content: self.content.clone(),
+            handlers: self.handlers.clone(),
+            spans: self.spans.clone(),
+            wrap: self.wrap,
+            overflow: self.overflow,
+        }
+    }
 }
 
 impl<A> Text<A> {
@@ -36,6 +171,47 @@ impl<A> Text<A> {
     pub fn handlers(&self) -> &Handlers<A> {
         &self.handlers
     }
+
+    pub fn spans(&self) -> &[Span<A>] {
+        &self.spans
+    }
+
+    /// Byte ranges of `spans` within `content`.
+    pub fn span_ranges(&self) -> Vec<Range<usize>> {
+        let mut start = 0;
+
+        self.spans
+            .iter()
+            .map(|span| {
+                let range = start..start + span.content.len();
+                start = range.end;
+                range
+            })
+            .collect()
+    }
+
+    /// Whether lines longer than the width the text gets are wrapped.
+    pub fn wraps(&self) -> bool {
+        self.wrap
+    }
+
+    pub fn overflow(&self) -> Overflow {
+        self.overflow
+    }
+
+    fn map<B>(&self, f: &Arc<dyn Fn(A) -> B>) -> Text<B>
+    where
+        A: 'static,
+        B: 'static,
+    {
+        Text {
+            content: self.content.clone(),
+            handlers: self.handlers.map(f),
+            spans: self.spans.iter().map(|span| span.map(f)).collect(),
+            wrap: self.wrap,
+            overflow: self.overflow,
+        }
+    }
 }
 
 pub struct Item<A> {
@@ -48,6 +224,20 @@ pub struct Item<A> {
     layout: ItemLayout,
 }
 
+impl<A> Clone for Item<A> {
+    fn clone(&self) -> Self {
+        Item {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            key: self.key.clone(),
+            classes: self.classes.clone(),
+            attributes: self.attributes.clone(),
+            handlers: self.handlers.clone(),
+            layout: self.layout.clone(),
+        }
+    }
+}
+
 impl<A> Item<A> {
     fn id(&self) -> Option<&str> {
         match self.id.as_ref() {
@@ -104,7 +294,27 @@ impl<A> Item<A> {
         }
     }
 
-    fn handlers(&self) -> &Handlers<A> {
+    pub fn handlers(&self) -> &Handlers<A> {
         &self.handlers
     }
+
+    pub(crate) fn layout(&self) -> &ItemLayout {
+        &self.layout
+    }
+
+    fn map<B>(&self, f: &Arc<dyn Fn(A) -> B>) -> Item<B>
+    where
+        A: 'static,
+        B: 'static,
+    {
+        Item {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            key: self.key.clone(),
+            classes: self.classes.clone(),
+            attributes: self.attributes.clone(),
+            handlers: self.handlers.map(f),
+            layout: self.layout.clone(),
+        }
+    }
 }
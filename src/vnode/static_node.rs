@@ -11,12 +11,25 @@ pub type StaticText<A> = Arc<Text<A>>;
 pub type StaticItem<A> = Arc<Item<A>>;
 
 pub type StaticChildren<A> = Vec<StaticNode<A>>;
+
+pub type SharedContainer<A> = Arc<StaticContainer<A>>;
+
 pub struct StaticContainer<A> {
     item: StaticItem<A>,
     children: StaticChildren<A>,
     layout: Arc<ContainerLayout>,
 }
 
+impl<A> Clone for StaticContainer<A> {
+    fn clone(&self) -> Self {
+        StaticContainer {
+            item: self.item.clone(),
+            children: self.children.clone(),
+            layout: self.layout.clone(),
+        }
+    }
+}
+
 impl<A> StaticContainer<A> {
     pub fn item(&self) -> &StaticItem<A> {
         &self.item
@@ -29,12 +42,30 @@ impl<A> StaticContainer<A> {
     pub fn layout(&self) -> &Arc<ContainerLayout> {
         &self.layout
     }
+
+    pub(super) fn item_mut(&mut self) -> &mut StaticItem<A> {
+        &mut self.item
+    }
+
+    pub(super) fn children_mut(&mut self) -> &mut StaticChildren<A> {
+        &mut self.children
+    }
 }
 
 pub enum StaticNode<A> {
     Text(StaticText<A>),
     Item(StaticItem<A>),
-    Container(StaticContainer<A>),
+    Container(SharedContainer<A>),
+}
+
+impl<A> Clone for StaticNode<A> {
+    fn clone(&self) -> Self {
+        match self {
+            StaticNode::Text(text) => StaticNode::Text(text.clone()),
+            StaticNode::Item(item) => StaticNode::Item(item.clone()),
+            StaticNode::Container(container) => StaticNode::Container(container.clone()),
+        }
+    }
 }
 
 impl<A> StaticNode<A> {
@@ -51,11 +82,101 @@ impl<A> StaticNode<A> {
         children: StaticChildren<A>,
         layout: Arc<ContainerLayout>,
     ) -> Self {
-        StaticNode::Container(StaticContainer {
+        StaticNode::Container(Arc::new(StaticContainer {
             item,
             children,
             layout,
-        })
+        }))
+    }
+
+    /// Converts every handler in the tree to produce `B` instead of `A`.
+    pub fn map<B>(&self, f: &Arc<dyn Fn(A) -> B>) -> StaticNode<B>
+    where
+        A: 'static,
+        B: 'static,
+    {
+        match self {
+            StaticNode::Text(text) => StaticNode::new_text(Arc::new(text.map(f))),
+            StaticNode::Item(item) => StaticNode::new_item(Arc::new(item.map(f))),
+            StaticNode::Container(container) => StaticNode::new_container(
+                Arc::new(container.item.map(f)),
+                container
+                    .children
+                    .iter()
+                    .map(|child| child.map(f))
+                    .collect(),
+                container.layout.clone(),
+            ),
+        }
+    }
+
+    /// Like `map`, where `last` is what `source` was mapped to. Subtrees
+    /// shared with `source` are not mapped again, but taken from `last`.
+    pub(crate) fn map_reusing<B>(
+        &self,
+        f: &Arc<dyn Fn(A) -> B>,
+        source: &StaticNode<A>,
+        last: &StaticNode<B>,
+    ) -> StaticNode<B>
+    where
+        A: 'static,
+        B: 'static,
+    {
+        if self.ptr_eq(source) {
+            return last.clone();
+        }
+
+        match (self, source, last) {
+            (
+                StaticNode::Container(container),
+                StaticNode::Container(source),
+                StaticNode::Container(last),
+            ) => {
+                let item = if Arc::ptr_eq(&container.item, &source.item) {
+                    last.item.clone()
+                } else {
+                    Arc::new(container.item.map(f))
+                };
+
+                let children: StaticChildren<B> = container
+                    .children
+                    .iter()
+                    .enumerate()
+                    .map(|(index, child)| {
+                        match (source.children.get(index), last.children.get(index)) {
+                            (Some(source), Some(last)) => child.map_reusing(f, source, last),
+                            _ => child.map(f),
+                        }
+                    })
+                    .collect();
+
+                let unchanged = Arc::ptr_eq(&item, &last.item)
+                    && Arc::ptr_eq(&container.layout, &last.layout)
+                    && children.len() == last.children.len()
+                    && children
+                        .iter()
+                        .zip(last.children.iter())
+                        .all(|(child, last)| child.ptr_eq(last));
+
+                if unchanged {
+                    StaticNode::Container(last.clone())
+                } else {
+                    StaticNode::new_container(item, children, container.layout.clone())
+                }
+            }
+            _ => self.map(f),
+        }
+    }
+
+    /// True when both nodes share the same allocation, which means the
+    /// subtrees are identical and comparing them can be skipped.
+    pub fn ptr_eq(&self, other: &StaticNode<A>) -> bool {
+        match (self, other) {
+            (StaticNode::Text(a), StaticNode::Text(b)) => Arc::ptr_eq(a, b),
+            (StaticNode::Item(a), StaticNode::Item(b)) => Arc::ptr_eq(a, b),
+            (StaticNode::Container(a), StaticNode::Container(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
     }
 }
 
@@ -0,0 +1,149 @@
+use std::ops::Range;
+
+use unicode_linebreak::{linebreaks, BreakOpportunity};
+use unicode_segmentation::UnicodeSegmentation;
+
+use layout::Overflow;
+use measure::TextMeasurer;
+
+pub(crate) const ELLIPSIS: &str = "\u{2026}";
+
+/// Splits `text` into the lines it is laid out in when it is `width` wide.
+///
+/// Newlines always end a line. With `wrap`, lines also end at the last
+/// Unicode line break opportunity that still fits, and words that do not
+/// fit on a line of their own are broken between grapheme clusters.
+/// Whitespace a line was wrapped at is not part of it.
+pub(crate) fn lines<'t>(
+    text: &'t str,
+    width: u16,
+    wrap: bool,
+    measurer: &dyn TextMeasurer,
+) -> Vec<&'t str> {
+    if !wrap {
+        let mut lines: Vec<_> = text
+            .split('\n')
+            .map(|line| line.trim_end_matches(is_newline))
+            .collect();
+        // A trailing newline ends the last line, as it does when wrapping.
+        if lines.len() > 1 && lines.last() == Some(&"") {
+            lines.pop();
+        }
+        return lines;
+    }
+
+    let fits = |line: &str| measurer.measure(line.trim_end()).0 <= width;
+
+    let mut lines = Vec::new();
+    let mut start = 0;
+    // The last break after `start` where the line would still fit.
+    let mut last = None;
+
+    for (index, opportunity) in linebreaks(text) {
+        while !fits(&text[start..index]) {
+            match last.take() {
+                Some(end) => {
+                    lines.push(text[start..end].trim_end());
+                    start = end;
+                }
+                None => {
+                    let rest = &text[start..index];
+                    let mut end = measurer.fit(rest, width);
+                    if end == 0 {
+                        end = rest.graphemes(true).next().map_or(rest.len(), str::len);
+                    }
+                    lines.push(&rest[..end]);
+                    start += end;
+                }
+            }
+        }
+
+        match opportunity {
+            BreakOpportunity::Mandatory => {
+                lines.push(text[start..index].trim_end_matches(is_newline));
+                start = index;
+                last = None;
+            }
+            BreakOpportunity::Allowed => last = Some(index),
+        }
+    }
+
+    lines
+}
+
+/// A part of a line left after truncating it.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Piece {
+    /// Byte range within the line.
+    Slice(Range<usize>),
+    Ellipsis,
+}
+
+/// Cuts `line` down to `width` as `overflow` says.
+pub(crate) fn truncate(
+    line: &str,
+    width: u16,
+    overflow: Overflow,
+    measurer: &dyn TextMeasurer,
+) -> Vec<Piece> {
+    let clip = |start: usize| Piece::Slice(start..start + measurer.fit(&line[start..], width));
+
+    if let Overflow::Scroll(offset) = overflow {
+        return vec![clip(skip(line, offset, measurer))];
+    }
+
+    let ellipsis = measurer.measure(ELLIPSIS).0;
+    if measurer.measure(line).0 <= width || ellipsis > width {
+        return vec![clip(0)];
+    }
+
+    let available = width - ellipsis;
+
+    match overflow {
+        Overflow::Ellipsis => vec![
+            Piece::Slice(0..measurer.fit(line, available)),
+            Piece::Ellipsis,
+        ],
+        Overflow::MiddleEllipsis => {
+            let head = measurer.fit(line, available - available / 2);
+            let rest = available - measurer.measure(&line[..head]).0;
+            let tail = fit_end(line, rest, measurer);
+            vec![
+                Piece::Slice(0..head),
+                Piece::Ellipsis,
+                Piece::Slice(tail..line.len()),
+            ]
+        }
+        _ => vec![clip(0)],
+    }
+}
+
+/// Byte index of the first grapheme cluster that starts at least `offset`
+/// into `line`.
+fn skip(line: &str, offset: u16, measurer: &dyn TextMeasurer) -> usize {
+    line.grapheme_indices(true)
+        .map(|(index, _)| index)
+        .find(|&index| measurer.measure(&line[..index]).0 >= offset)
+        .unwrap_or(line.len())
+}
+
+/// Byte index where the longest end of `line` at most `width` wide starts.
+fn fit_end(line: &str, width: u16, measurer: &dyn TextMeasurer) -> usize {
+    let mut start = line.len();
+
+    for (index, _) in line.grapheme_indices(true).rev() {
+        if measurer.measure(&line[index..]).0 > width {
+            break;
+        }
+        start = index;
+    }
+
+    start
+}
+
+fn is_newline(character: char) -> bool {
+    matches!(
+        character,
+        '\n' | '\r' | '\u{0B}' | '\u{0C}' | '\u{85}' | '\u{2028}' | '\u{2029}'
+    )
+}
@@ -5,9 +5,13 @@ extern crate cinnabar;
 
 mod helper;
 
-use self::helper::{Action, Message, Store};
+use std::sync::Arc;
 
-use cinnabar::{App, Template};
+use self::helper::{reducer, Action, Message, Store};
+
+use cinnabar::backend::{Event, FakeBackend};
+use cinnabar::state::{LocalAction, LocalHandle, StateKey};
+use cinnabar::{run, App, Direction, Template, TemplateBuilder};
 
 elements_for!(Store, Message, Action);
 
@@ -61,3 +65,125 @@ fn simple_app_test() {
 "#
     );
 }
+
+struct Toggle;
+
+#[test]
+fn local_state_test() {
+    let store = Store { points: 0 };
+
+    let dropdown = Template::stateful(
+        || false,
+        |open: bool, _action: Toggle| !open,
+        |_store: &Store, _message: &Option<Message>, open: &bool, _local: &LocalHandle<Toggle>| {
+            if *open {
+                panel().child(text("Open")).done()
+            } else {
+                text("Closed").done()
+            }
+        },
+    );
+
+    let view = panel()
+        .child(TemplateBuilder::from(dropdown.clone()).key("menu"))
+        .child(dropdown.clone())
+        .done();
+
+    let mut app = App::new(store, view, |_store, _action| {
+        panic!("Local actions should not reach the reducer")
+    });
+
+    assert_eq!(format!("{}", app.view()), "<panel>ClosedClosed</panel>");
+
+    app = app.local_action("menu", Toggle);
+
+    assert_eq!(
+        format!("{}", app.view()),
+        "<panel><panel>Open</panel>Closed</panel>"
+    );
+
+    app = app.local_action(vec![1], Toggle);
+    app = app.local_action("menu", Toggle);
+
+    assert_eq!(
+        format!("{}", app.view()),
+        "<panel>Closed<panel>Open</panel></panel>"
+    );
+}
+
+#[test]
+fn local_actions_from_handlers() {
+    let dropdown = Template::stateful(
+        || false,
+        |open: bool, _action: Toggle| !open,
+        |_store: &Store, _message: &Option<Message>, open: &bool, local: &LocalHandle<Toggle>| {
+            let local = local.clone();
+            text(if *open { "[-]" } else { "[+]" })
+                .on_local_click(move |_| local.action(Toggle))
+                .done()
+        },
+    );
+
+    // Keys only have to be unique among siblings.
+    let list = |dropdown: &Arc<Template<Store, Message, Action>>| {
+        panel()
+            .child(TemplateBuilder::from(dropdown.clone()).key("row1"))
+            .child(TemplateBuilder::from(dropdown.clone()).key("row2"))
+    };
+    let view = panel()
+        .direction(Direction::Column)
+        .child(list(&dropdown))
+        .child(list(&dropdown))
+        .done();
+
+    let app = App::new(Store { points: 0 }, view, |_store, _action| {
+        panic!("Local actions should not reach the reducer")
+    });
+
+    let mut backend = FakeBackend::new((10, 2))
+        .event(Event::Click((0, 1)))
+        .idle()
+        .event(Event::Click((3, 0)))
+        .idle();
+    let mut app = run(app, &mut backend).unwrap();
+
+    assert_eq!(backend.screen(), &["[+][-]", "[-][+]"]);
+
+    app.dispatch_local(LocalAction::new(StateKey::scoped(vec![1], "row1"), Toggle));
+    assert_eq!(
+        format!("{}", app.view()),
+        "<panel><panel>[+][-]</panel><panel>[+][+]</panel></panel>"
+    );
+}
+
+#[test]
+fn memo_templates_shared_between_apps() {
+    let toggle = Template::stateful(
+        || false,
+        |open: bool, _action: Toggle| !open,
+        |_store: &Store, _message: &Option<Message>, open: &bool, _local: &LocalHandle<Toggle>| {
+            text(if *open { "open" } else { "closed" }).done()
+        },
+    );
+    let memo = Template::memo(
+        |store: &Store, _message: &Option<Message>| store.points,
+        move |_store: &Store, _message: &Option<Message>| {
+            TemplateBuilder::from(toggle.clone()).done()
+        },
+    );
+
+    // Both Apps keep their own last render, and with it their own state.
+    let app = || {
+        App::new(
+            Store { points: 0 },
+            panel().child(memo.clone()).done(),
+            reducer,
+        )
+    };
+    let (first, second) = (app(), app());
+    let first = first.local_action(vec![0, 0], Toggle);
+    let second = second.local_action(vec![0, 0], Toggle);
+
+    assert_eq!(format!("{}", first.view()), "<panel>open</panel>");
+    assert_eq!(format!("{}", second.view()), "<panel>open</panel>");
+}
@@ -0,0 +1,99 @@
+#[macro_use]
+extern crate pretty_assertions;
+#[macro_use]
+extern crate cinnabar;
+
+use std::thread;
+use std::time::Duration;
+
+use cinnabar::command::{FakeExecutor, ThreadExecutor};
+use cinnabar::{App, Command, Template};
+
+struct Store {
+    loading: bool,
+    value: Option<usize>,
+}
+
+enum Message {}
+
+enum Action {
+    Fetch,
+    Loaded(usize),
+}
+
+elements_for!(Store, Message, Action);
+
+fn view() -> ::std::sync::Arc<Template<Store, Message, Action>> {
+    Template::new(|store: &Store, _message: &Option<Message>| {
+        match (store.loading, store.value) {
+            (true, _) => text("Loading"),
+            (false, Some(value)) => text(format!("Loaded {}", value)),
+            (false, None) => text("Empty"),
+        }
+        .done()
+    })
+}
+
+fn reducer(store: Store, action: Action) -> (Store, Vec<Command<Action>>) {
+    match action {
+        Action::Fetch => (
+            Store {
+                loading: true,
+                ..store
+            },
+            vec![Command::new(|| Action::Loaded(42))],
+        ),
+        Action::Loaded(value) => (
+            Store {
+                loading: false,
+                value: Some(value),
+            },
+            Vec::new(),
+        ),
+    }
+}
+
+#[test]
+fn fake_executor_commands() {
+    let executor = FakeExecutor::new();
+    let store = Store {
+        loading: false,
+        value: None,
+    };
+
+    let mut app = App::with_commands(store, view(), reducer, executor.clone());
+    app = app.action(Action::Fetch);
+
+    assert_eq!(executor.pending(), 1);
+    assert_eq!(format!("{}", app.view()), "Loading");
+
+    app = app.poll();
+    assert_eq!(format!("{}", app.view()), "Loading");
+
+    assert_eq!(executor.run_all(), 1);
+    app = app.poll();
+
+    assert_eq!(executor.pending(), 0);
+    assert_eq!(format!("{}", app.view()), "Loaded 42");
+}
+
+#[test]
+fn thread_executor_commands() {
+    let store = Store {
+        loading: false,
+        value: None,
+    };
+
+    let mut app = App::with_commands(store, view(), reducer, ThreadExecutor::new());
+    app = app.action(Action::Fetch);
+
+    for _ in 0..100 {
+        app = app.poll();
+        if format!("{}", app.view()) != "Loading" {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    assert_eq!(format!("{}", app.view()), "Loaded 42");
+}
@@ -0,0 +1,185 @@
+#[macro_use]
+extern crate pretty_assertions;
+#[macro_use]
+extern crate cinnabar;
+
+mod helper;
+
+use self::helper::{Action, Message, Store};
+
+use cinnabar::event::ClickEvent;
+use cinnabar::render::RenderCommand;
+use cinnabar::vnode::diff::{apply, diff, DuplicateKey};
+use cinnabar::vnode::{DynamicNode, StaticNode};
+use cinnabar::{render_list, Direction};
+
+elements_for!(Store, Message, Action);
+
+fn render<T: Into<DynamicNode<Store, Message, Action>>>(view: T) -> StaticNode<Action> {
+    view.into().render(&Store { points: 0 })
+}
+
+fn assert_patches(old: &StaticNode<Action>, new: &StaticNode<Action>, expected: &[&str]) {
+    let patches = diff(old, new).unwrap();
+    let described: Vec<String> = patches.iter().map(|patch| format!("{:?}", patch)).collect();
+    assert_eq!(described, expected);
+
+    let mut patched = old.clone();
+    apply(&mut patched, patches);
+    assert_eq!(format!("{:?}", patched), format!("{:?}", new));
+}
+
+#[test]
+fn diff_text_and_attributes() {
+    let old = render(
+        panel()
+            .child(text("0 points"))
+            .child(button().class("primary").attr("hidden", "")),
+    );
+    let new = render(
+        panel()
+            .child(text("1 points"))
+            .child(button().class("secondary").attr("title", "OK")),
+    );
+
+    assert_patches(
+        &old,
+        &new,
+        &[
+            "SetText [0] 1 points",
+            "SetAttribute [1] class=\"secondary\"",
+            "RemoveAttribute [1] hidden",
+            "SetAttribute [1] title=\"OK\"",
+        ],
+    );
+}
+
+#[test]
+fn diff_keyed_children() {
+    let old = render(
+        panel()
+            .child(button().key("a"))
+            .child(button().key("b"))
+            .child(button().key("c")),
+    );
+    let new = render(
+        panel()
+            .child(button().key("c"))
+            .child(button().key("a"))
+            .child(button().key("d")),
+    );
+
+    assert_patches(
+        &old,
+        &new,
+        &[
+            "Remove [][1]",
+            "Insert [][2] <button key=\"d\"></button>",
+            "Move [][1] -> [0]",
+        ],
+    );
+}
+
+#[test]
+fn diff_replace() {
+    let old = render(panel().child(button().child(text("OK"))));
+    let new = render(panel().child(text("OK")));
+
+    assert_patches(&old, &new, &["Replace [0] OK"]);
+}
+
+#[test]
+fn diff_keyed_moves_are_minimal() {
+    let rows = |order: &[usize]| {
+        let mut table = panel();
+        for row in order {
+            table = table.child(button().key(format!("row-{}", row)));
+        }
+        render(table)
+    };
+
+    let old = rows(&(0..100).collect::<Vec<_>>());
+    let mut order: Vec<usize> = (1..100).collect();
+    order.push(0);
+    order.swap(10, 60);
+    let new = rows(&order);
+
+    assert_patches(
+        &old,
+        &new,
+        &[
+            "Move [][0] -> [99]",
+            "Move [][10] -> [60]",
+            "Move [][59] -> [10]",
+        ],
+    );
+}
+
+#[test]
+fn diff_duplicate_keys() {
+    let old = render(panel().child(button().key("a")));
+    let new = render(
+        panel()
+            .child(button().key("a"))
+            .child(panel().child(button().key("b")).child(button().key("b"))),
+    );
+
+    assert_eq!(
+        diff(&old, &new).err(),
+        Some(DuplicateKey {
+            parent: vec![1],
+            key: "b".into(),
+        })
+    );
+}
+
+#[test]
+fn diff_layout_and_handlers() {
+    let view = |direction| {
+        panel()
+            .direction(direction)
+            .child(text("a"))
+            .child(text("b"))
+    };
+    let old = render(view(Direction::Row));
+    let new = render(view(Direction::Column));
+
+    assert_patches(&old, &new, &["Replace [] <panel>ab</panel>"]);
+
+    let mut patched = old.clone();
+    apply(&mut patched, diff(&old, &new).unwrap());
+    let positions = |node: &StaticNode<Action>| -> Vec<(u16, u16)> {
+        render_list(node, (0, 0), (10, 10))
+            .iter()
+            .map(|command| match command {
+                RenderCommand::Text(text) => text.position,
+            })
+            .collect()
+    };
+    assert_eq!(positions(&patched), vec![(0, 0), (0, 1)]);
+
+    // Adding a handler replaces the node, since its closure can not be patched in.
+    let old = render(panel().child(button().child(text("OK"))));
+    let new = render(panel().child(button().on_click(|_| Action::Increment).child(text("OK"))));
+
+    assert_patches(&old, &new, &["Replace [0] <button>OK</button>"]);
+}
+
+#[test]
+fn diff_changed_handlers() {
+    let row = |label: &'static str, action: fn() -> Action| {
+        render(panel().child(button().on_click(move |_| action()).child(text(label))))
+    };
+    let old = row("Row 1", || Action::None);
+    let new = row("Row 2", || Action::Increment);
+
+    assert_patches(&old, &new, &["SetHandlers [0]", "SetText [0, 0] Row 2"]);
+
+    // Clicking the patched row sends the action of the new one.
+    let mut patched = old.clone();
+    apply(&mut patched, diff(&old, &new).unwrap());
+    let clicked = match render_list(&patched, (0, 0), (10, 1))[0] {
+        RenderCommand::Text(ref text) => (text.handlers().click.as_ref().unwrap())(ClickEvent {}),
+    };
+    assert_eq!(format!("{:?}", clicked), "Increment");
+}
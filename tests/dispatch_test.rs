@@ -0,0 +1,95 @@
+#[macro_use]
+extern crate pretty_assertions;
+#[macro_use]
+extern crate cinnabar;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use cinnabar::command::Executor;
+use cinnabar::{App, Command, Template};
+
+struct Store {
+    log: Vec<String>,
+}
+
+enum Message {}
+
+enum Action {
+    Push(&'static str),
+    Chain(&'static str, &'static str),
+}
+
+elements_for!(Store, Message, Action);
+
+/// Runs commands as soon as they are executed.
+struct SyncExecutor {
+    finished: Vec<Action>,
+}
+
+impl Executor<Action> for SyncExecutor {
+    fn execute(&mut self, command: Command<Action>) {
+        self.finished.push(command.run());
+    }
+
+    fn poll(&mut self) -> Vec<Action> {
+        self.finished.drain(..).collect()
+    }
+}
+
+fn app(renders: Arc<AtomicUsize>) -> App<Store, Message, Action> {
+    let view = Template::new(move |store: &Store, _message: &Option<Message>| {
+        renders.fetch_add(1, Ordering::SeqCst);
+        text(store.log.join(" ")).done()
+    });
+
+    let reducer = |mut store: Store, action| match action {
+        Action::Push(entry) => {
+            store.log.push(entry.to_string());
+            (store, Vec::new())
+        }
+        Action::Chain(entry, next) => {
+            store.log.push(entry.to_string());
+            (store, vec![Command::new(move || Action::Push(next))])
+        }
+    };
+
+    let executor = SyncExecutor {
+        finished: Vec::new(),
+    };
+
+    App::with_commands(Store { log: Vec::new() }, view, reducer, executor)
+}
+
+#[test]
+fn dispatch_drains_queue_before_rendering() {
+    let renders = Arc::new(AtomicUsize::new(0));
+    let mut app = app(renders.clone());
+    assert_eq!(renders.load(Ordering::SeqCst), 1);
+
+    app.enqueue(Action::Push("a"));
+    app.dispatch(Action::Chain("b", "d"));
+    app.dispatch(Action::Push("e"));
+
+    assert_eq!(format!("{}", app.view()), "a b d e");
+    assert_eq!(renders.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn dispatch_all_renders_once() {
+    let renders = Arc::new(AtomicUsize::new(0));
+    let mut app = app(renders.clone());
+
+    app.dispatch_all(vec![
+        Action::Push("a"),
+        Action::Chain("b", "c"),
+        Action::Push("d"),
+    ]);
+
+    // Command actions are queued behind the ones already waiting.
+    assert_eq!(format!("{}", app.view()), "a b d c");
+    assert_eq!(renders.load(Ordering::SeqCst), 2);
+
+    app.flush();
+    assert_eq!(renders.load(Ordering::SeqCst), 2);
+}
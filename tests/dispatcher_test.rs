@@ -0,0 +1,76 @@
+#[macro_use]
+extern crate pretty_assertions;
+#[macro_use]
+extern crate cinnabar;
+
+use std::thread;
+
+use cinnabar::{App, Template};
+
+struct Store {
+    scanned: usize,
+    done: bool,
+}
+
+enum Message {}
+
+enum Action {
+    Scanned(usize),
+    Done,
+}
+
+elements_for!(Store, Message, Action);
+
+#[test]
+fn dispatch_from_worker_threads() {
+    let view = Template::new(|store: &Store, _message: &Option<Message>| {
+        let status = if store.done { "done" } else { "scanning" };
+        text(format!("{} files, {}", store.scanned, status)).done()
+    });
+
+    let store = Store {
+        scanned: 0,
+        done: false,
+    };
+
+    let mut app = App::new(store, view, |store, action| match action {
+        Action::Scanned(count) => Store {
+            scanned: store.scanned + count,
+            ..store
+        },
+        Action::Done => Store {
+            done: true,
+            ..store
+        },
+    });
+
+    let workers: Vec<_> = (0..4)
+        .map(|_| {
+            let dispatcher = app.dispatcher();
+            thread::spawn(move || {
+                for _ in 0..10 {
+                    assert!(dispatcher.dispatch(Action::Scanned(1)));
+                }
+            })
+        })
+        .collect();
+
+    for worker in workers {
+        worker.join().unwrap();
+    }
+    app.dispatcher().dispatch(Action::Done);
+
+    assert_eq!(app.run_pending(), 41);
+    assert_eq!(format!("{}", app.view()), "40 files, done");
+    assert_eq!(app.run_pending(), 0);
+
+    let dispatcher = app.dispatcher();
+    drop(app);
+    assert!(!dispatcher.dispatch(Action::Done));
+}
+
+#[test]
+fn dispatcher_is_thread_safe() {
+    fn thread_safe<T: Clone + Send + Sync>() {}
+    thread_safe::<cinnabar::Dispatcher<Action>>();
+}
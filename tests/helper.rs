@@ -1,9 +1,22 @@
+#[derive(Clone)]
 pub struct Store {
     pub points: usize,
 }
 
 pub enum Message {}
+#[derive(Clone, Debug)]
 pub enum Action {
     None,
     Increment,
 }
+
+// Not every test uses it.
+#[allow(dead_code)]
+pub fn reducer(store: Store, action: Action) -> Store {
+    match action {
+        Action::Increment => Store {
+            points: store.points + 1,
+        },
+        Action::None => store,
+    }
+}
@@ -0,0 +1,78 @@
+#[macro_use]
+extern crate pretty_assertions;
+#[macro_use]
+extern crate cinnabar;
+
+mod helper;
+
+use self::helper::{reducer, Action, Message, Store};
+
+use cinnabar::middleware::Next;
+use cinnabar::{App, Template};
+
+elements_for!(Store, Message, Action);
+
+#[test]
+fn undo_redo_and_jump() {
+    let counter = Template::new(|store: &Store, _message: &Option<Message>| {
+        text(format!("{}", store.points)).done()
+    });
+
+    let mut app = App::new(Store { points: 0 }, counter, reducer).enable_history();
+
+    app = app.action(Action::Increment);
+    app = app.action(Action::Increment);
+    app = app.action(Action::Increment);
+    assert_eq!(format!("{}", app.view()), "3");
+
+    app = app.undo().undo();
+    assert_eq!(format!("{}", app.view()), "1");
+
+    app = app.redo();
+    assert_eq!(format!("{}", app.view()), "2");
+
+    app = app.jump(0);
+    assert_eq!(format!("{}", app.view()), "0");
+    assert_eq!(app.history().unwrap().len(), 4);
+
+    // Dispatching after going back drops the undone entries.
+    app = app.action(Action::None);
+    assert_eq!(format!("{}", app.view()), "0");
+
+    let history = app.history().unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history.cursor(), 1);
+    assert!(!history.can_redo());
+    assert_eq!(format!("{:?}", history.actions()), "[None, Some(None)]");
+}
+
+#[test]
+fn history_skips_rejected_actions_and_keeps_capacity() {
+    let counter = Template::new(|store: &Store, _message: &Option<Message>| {
+        text(format!("{}", store.points)).done()
+    });
+
+    let mut app = App::new(Store { points: 0 }, counter, reducer)
+        // Reject increments past 4.
+        .middleware(
+            |store: Store, action: Action, next: Next<Store, Action>| match action {
+                Action::Increment if store.points >= 4 => store,
+                action => next(store, action),
+            },
+        )
+        .enable_history_with_capacity(3);
+
+    for _ in 0..6 {
+        app = app.action(Action::Increment);
+    }
+    assert_eq!(format!("{}", app.view()), "4");
+
+    let history = app.history().unwrap();
+    assert_eq!(history.capacity(), Some(3));
+    assert_eq!(history.len(), 3);
+    assert_eq!(history.cursor(), 2);
+    assert_eq!(history.store(0).unwrap().points, 2);
+
+    app = app.undo().undo().undo();
+    assert_eq!(format!("{}", app.view()), "2");
+}
@@ -0,0 +1,151 @@
+#[macro_use]
+extern crate pretty_assertions;
+#[macro_use]
+extern crate cinnabar;
+
+use cinnabar::event::ClickEvent;
+use cinnabar::vnode::{DynamicNode, StaticNode};
+use cinnabar::{App, Template};
+
+mod counter {
+    use cinnabar::vnode::DynamicNode;
+    use cinnabar::Template;
+
+    pub struct Store {
+        pub count: usize,
+    }
+
+    pub enum Message {}
+
+    pub enum Action {
+        Increment,
+    }
+
+    elements_for!(Store, Message, Action);
+
+    pub fn view() -> DynamicNode<Store, Message, Action> {
+        Template::new(|store: &Store, _message: &Option<Message>| {
+            text(format!("Clicked {} times", store.count))
+                .on_click(|_| Action::Increment)
+                .done()
+        })
+        .into()
+    }
+
+    pub fn reducer(store: Store, action: Action) -> Store {
+        match action {
+            Action::Increment => Store {
+                count: store.count + 1,
+            },
+        }
+    }
+}
+
+struct Store {
+    title: String,
+    counter: counter::Store,
+}
+
+enum Message {}
+
+enum Action {
+    Counter(counter::Action),
+}
+
+elements_for!(Store, Message, Action);
+
+#[test]
+fn mapped_component() {
+    let view = Template::new(|store: &Store, _message: &Option<Message>| {
+        panel()
+            .child(text(store.title.clone()))
+            .child(DynamicNode::map(
+                counter::view(),
+                |store: &Store| &store.counter,
+                Action::Counter,
+            ))
+            .done()
+    });
+
+    let mut app = App::new(
+        Store {
+            title: "Counter".to_string(),
+            counter: counter::Store { count: 0 },
+        },
+        view,
+        |store, action| match action {
+            Action::Counter(action) => Store {
+                counter: counter::reducer(store.counter, action),
+                ..store
+            },
+        },
+    );
+
+    assert_eq!(
+        format!("{}", app.view()),
+        "<panel>CounterClicked 0 times</panel>"
+    );
+
+    let action = match app.view() {
+        StaticNode::Container(container) => match container.children()[1] {
+            StaticNode::Text(ref text) => (text.handlers().click.as_ref().unwrap())(ClickEvent {}),
+            _ => panic!("Expected a text node"),
+        },
+        _ => panic!("Expected a container"),
+    };
+
+    app = app.action(action);
+
+    assert_eq!(
+        format!("{}", app.view()),
+        "<panel>CounterClicked 1 times</panel>"
+    );
+}
+
+#[test]
+fn mapped_component_keeps_sharing() {
+    let widget = || {
+        counter::panel()
+            .child(counter::text("Counter"))
+            .child(counter::view())
+            .done()
+    };
+    let view = panel()
+        .child(DynamicNode::map(
+            widget(),
+            |store: &Store| &store.counter,
+            Action::Counter,
+        ))
+        .done();
+
+    let mut app = App::new(
+        Store {
+            title: "Counter".to_string(),
+            counter: counter::Store { count: 0 },
+        },
+        view,
+        |store, action| match action {
+            Action::Counter(action) => Store {
+                counter: counter::reducer(store.counter, action),
+                ..store
+            },
+        },
+    );
+
+    let title = |view: &StaticNode<Action>| match view {
+        StaticNode::Container(container) => match container.children()[0] {
+            StaticNode::Container(ref widget) => widget.children()[0].clone(),
+            _ => panic!("Expected a container"),
+        },
+        _ => panic!("Expected a container"),
+    };
+
+    let before = title(app.view());
+    app = app.action(Action::Counter(counter::Action::Increment));
+
+    assert_eq!(
+        format!("{}", app.view()),
+        "<panel><panel>CounterClicked 1 times</panel></panel>"
+    );
+    assert!(title(app.view()).ptr_eq(&before));
+}
@@ -0,0 +1,181 @@
+#![cfg(feature = "markdown")]
+
+#[macro_use]
+extern crate pretty_assertions;
+#[macro_use]
+extern crate cinnabar;
+
+mod helper;
+
+use self::helper::{reducer, Action, Message, Store};
+
+use cinnabar::backend::{Event, FakeBackend};
+use cinnabar::markdown::{markdown, markdown_with_links};
+use cinnabar::vnode::StaticNode;
+use cinnabar::{run, App, Template, Weight};
+
+elements_for!(Store, Message, Action);
+
+const HELP: &str = "# Help
+
+Press *plus* to earn **points**.
+
+- Click [more](points) for one
+- Or press `+`
+
+> Points never expire.
+
+```rust
+let points = 1;
+```
+";
+
+#[test]
+fn convert_blocks() {
+    let node = markdown::<Store, Message, Action>(HELP)
+        .done()
+        .render(&Store { points: 0 });
+
+    assert_eq!(
+        format!("{:?}", node),
+        r#"<markdown>
+    <heading class="h1">
+        Help
+    </heading>
+    <paragraph>
+        Press plus to earn points.
+    </paragraph>
+    <list class="bullet">
+        <item>
+            • 
+            <body>
+                Click more for one
+            </body>
+        </item>
+        <item>
+            • 
+            <body>
+                Or press +
+            </body>
+        </item>
+    </list>
+    <quote>
+        <paragraph>
+            Points never expire.
+        </paragraph>
+    </quote>
+    <code class="lang-rust">
+        let points = 1;
+    </code>
+</markdown>"#
+    );
+
+    let node = markdown::<Store, Message, Action>("3. three\n4. four")
+        .done()
+        .render(&Store { points: 0 });
+    assert_eq!(
+        format!("{}", node),
+        "<markdown><list class=\"ordered\">\
+         <item>3. <body>three</body></item>\
+         <item>4. <body>four</body></item>\
+         </list></markdown>"
+    );
+}
+
+#[test]
+fn inline_styles() {
+    let node = markdown::<Store, Message, Action>("Press *plus* to earn **points**.")
+        .done()
+        .render(&Store { points: 0 });
+
+    let paragraph = match node {
+        StaticNode::Container(markdown) => markdown.children()[0].clone(),
+        _ => panic!("Expected a container"),
+    };
+    let spans = match paragraph {
+        StaticNode::Container(paragraph) => match paragraph.children()[0] {
+            StaticNode::Text(ref text) => text.spans().to_vec(),
+            _ => panic!("Expected text"),
+        },
+        _ => panic!("Expected a container"),
+    };
+
+    let styles: Vec<(&str, bool, Weight)> = spans
+        .iter()
+        .map(|span| (span.content(), span.style().italic, span.style().weight))
+        .collect();
+
+    assert_eq!(
+        styles,
+        vec![
+            ("Press ", false, Weight::Normal),
+            ("plus", true, Weight::Normal),
+            (" to earn ", false, Weight::Normal),
+            ("points", false, Weight::Bold),
+            (".", false, Weight::Normal),
+        ]
+    );
+}
+
+#[test]
+fn inline_classes() {
+    let node = markdown::<Store, Message, Action>("Press `+` or [**more**](points)")
+        .done()
+        .render(&Store { points: 0 });
+
+    let paragraph = match node {
+        StaticNode::Container(markdown) => markdown.children()[0].clone(),
+        _ => panic!("Expected a container"),
+    };
+    let spans = match paragraph {
+        StaticNode::Container(paragraph) => match paragraph.children()[0] {
+            StaticNode::Text(ref text) => text.spans().to_vec(),
+            _ => panic!("Expected text"),
+        },
+        _ => panic!("Expected a container"),
+    };
+
+    let classes: Vec<(&str, Vec<&str>)> = spans
+        .iter()
+        .map(|span| {
+            let mut classes: Vec<&str> = span.classes().iter().map(|c| c.as_ref()).collect();
+            classes.sort();
+            (span.content(), classes)
+        })
+        .collect();
+
+    assert_eq!(
+        classes,
+        vec![
+            ("Press ", vec![]),
+            ("+", vec!["code"]),
+            (" or ", vec![]),
+            ("more", vec!["link", "strong"]),
+        ]
+    );
+    assert!(spans[3].style().underline);
+    assert_eq!(spans[3].style().weight, Weight::Bold);
+}
+
+#[test]
+fn click_link() {
+    let help = Template::new(|_store: &Store, _message: &Option<Message>| {
+        markdown_with_links("See [more](points)", |url| match url {
+            "points" => Action::Increment,
+            _ => Action::None,
+        })
+        .done()
+    });
+    let app = App::new(Store { points: 0 }, help, reducer);
+
+    let mut backend = FakeBackend::new((20, 1))
+        .event(Event::Click((1, 0)))
+        .idle()
+        .event(Event::Click((5, 0)))
+        .idle();
+
+    let app = run(app, &mut backend).unwrap();
+
+    assert_eq!(app.store().points, 1);
+    assert_eq!(backend.screen(), &["See more".to_string()]);
+}
@@ -0,0 +1,115 @@
+#[macro_use]
+extern crate pretty_assertions;
+extern crate cinnabar;
+
+mod helper;
+
+use std::env;
+use std::fs;
+
+use self::helper::{reducer, Action, Message, Store};
+
+use cinnabar::markup::MarkupError;
+use cinnabar::{App, Bindings, Markup};
+
+fn bindings() -> Bindings<Store> {
+    Bindings::new().bind("store.points", |store: &Store| store.points)
+}
+
+#[test]
+fn markup_template() {
+    let markup = Markup::parse(
+        r#"
+        <panel class="counter">
+            {store.points} points
+            <button key="inc">Increment</button>
+            <icon name="star"/>
+        </panel>
+        "#,
+        bindings(),
+    )
+    .unwrap();
+
+    let mut app = App::new(
+        Store { points: 0 },
+        markup.template::<Message, Action>(),
+        reducer,
+    );
+
+    app = app.action(Action::Increment);
+
+    assert_eq!(
+        format!("\n{:?}\n", app.view()),
+        r#"
+<panel class="counter">
+    1 points
+    <button key="inc">
+        Increment
+    </button>
+    <icon name="star">
+</panel>
+"#
+    );
+}
+
+#[test]
+fn markup_reload() {
+    let path = env::temp_dir().join(format!("cinnabar-markup-{}.markup", std::process::id()));
+    fs::write(&path, "<panel>{store.points}</panel>").unwrap();
+
+    let markup = Markup::load(&path, bindings()).unwrap();
+    let mut app = App::new(
+        Store { points: 7 },
+        markup.template::<Message, Action>(),
+        |store, _action| store,
+    );
+
+    assert_eq!(format!("{}", app.view()), "<panel>7</panel>");
+
+    fs::write(
+        &path,
+        "<panel><button>{store.points} points</button></panel>",
+    )
+    .unwrap();
+    markup.reload().unwrap();
+    app = app.refresh();
+
+    assert_eq!(
+        format!("{}", app.view()),
+        "<panel><button>7 points</button></panel>"
+    );
+
+    fs::write(&path, "<panel>{store.missing}</panel>").unwrap();
+    match markup.reload() {
+        Err(MarkupError::UnknownBinding(name)) => assert_eq!(name, "store.missing"),
+        _ => panic!("Expected an unknown binding error"),
+    }
+    app = app.refresh();
+
+    assert_eq!(
+        format!("{}", app.view()),
+        "<panel><button>7 points</button></panel>"
+    );
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn markup_syntax_error() {
+    match Markup::parse("<panel>\n  <button>OK</panel>", bindings()) {
+        Err(MarkupError::Syntax { line, .. }) => assert_eq!(line, 2),
+        _ => panic!("Expected a syntax error"),
+    }
+}
+
+#[test]
+fn markup_placeholder_errors() {
+    let position = |source: &str| match Markup::parse(source, bindings()) {
+        Err(MarkupError::Syntax { line, column, .. }) => (line, column),
+        _ => panic!("Expected a syntax error"),
+    };
+
+    assert_eq!(position("<panel>{store.points</panel>"), (1, 8));
+    assert_eq!(position("<panel>\n  {store.points} of {}</panel>"), (2, 21));
+    assert_eq!(position("<panel title=\"a } b\"/>"), (1, 17));
+}
@@ -0,0 +1,108 @@
+#[macro_use]
+extern crate pretty_assertions;
+#[macro_use]
+extern crate cinnabar;
+
+mod helper;
+
+use self::helper::{Action, Message, Store};
+
+use cinnabar::backend::{Backend, FakeBackend};
+use cinnabar::measure::{BitmapFont, TerminalMeasurer, TextMeasurer};
+use cinnabar::render::RenderCommand;
+use cinnabar::{render_list, render_list_with, run, App, Template};
+
+elements_for!(Store, Message, Action);
+
+type Run = (String, (u16, u16), (u16, u16));
+
+fn runs(list: &[RenderCommand<Action>]) -> Vec<Run> {
+    list.iter()
+        .map(|command| match command {
+            RenderCommand::Text(text) => (text.content.to_string(), text.position, text.size),
+        })
+        .collect()
+}
+
+#[test]
+fn terminal_measurer() {
+    assert_eq!(TerminalMeasurer.measure("points"), (6, 1));
+    assert_eq!(TerminalMeasurer.measure("héllo"), (5, 1));
+    assert_eq!(TerminalMeasurer.fit("héllo", 2), 3);
+    assert_eq!(TerminalMeasurer.fit("héllo", 10), 6);
+}
+
+#[test]
+fn terminal_measurer_unicode() {
+    assert_eq!(TerminalMeasurer.measure("日本語"), (6, 1));
+    assert_eq!(TerminalMeasurer.measure("e\u{301}te\u{301}"), (3, 1));
+    assert_eq!(TerminalMeasurer.measure("👍🏽!"), (3, 1));
+    assert_eq!(TerminalMeasurer.measure("👨‍👩‍👧"), (2, 1));
+
+    // A wide character that only half fits is left out.
+    assert_eq!(TerminalMeasurer.fit("日本語", 5), "日本".len());
+    assert_eq!(TerminalMeasurer.fit("e\u{301}x", 1), "e\u{301}".len());
+}
+
+#[test]
+fn wide_characters_on_cell_grid() {
+    let view = Template::new(|_store: &Store, _message: &Option<Message>| {
+        panel().child(text("日本語")).child(text("テキスト")).done()
+    });
+    let app = App::new(Store { points: 0 }, view, |store, _action| store);
+
+    let mut backend = FakeBackend::new((11, 1));
+    run(app, &mut backend).unwrap();
+
+    assert_eq!(backend.screen(), &["日本語テキ".to_string()]);
+
+    // A wide character past the edge ends the run.
+    let view = text("abcd日e").wrap(false).done();
+    let list = render_list(&view.render(&Store { points: 0 }), (0, 0), (10, 1));
+    let mut backend = FakeBackend::new((5, 1));
+    backend.present(&list).unwrap();
+
+    assert_eq!(backend.screen(), &["abcd".to_string()]);
+}
+
+#[test]
+fn bitmap_font() {
+    let font = BitmapFont::new(6, 10).glyph('i', 2).glyph('m', 10);
+
+    assert_eq!(font.measure("mini"), (20, 10));
+    assert_eq!(font.fit("mini", 13), 2);
+    assert_eq!(font.fit("mini", 11), 1);
+    assert_eq!(font.fit("mini", 9), 0);
+}
+
+#[test]
+fn layout_with_measurer() {
+    let view = Template::new(|_store: &Store, _message: &Option<Message>| {
+        panel()
+            .child(text("mini"))
+            .child(text("max").wrap(false))
+            .done()
+    });
+    let font = BitmapFont::new(6, 10).glyph('i', 2).glyph('m', 10);
+
+    let mut app =
+        App::new(Store { points: 0 }, view, |store, _action| store).measurer(font.clone());
+    app.resize((30, 20));
+
+    assert_eq!(
+        runs(app.layout()),
+        vec![
+            ("mini".to_string(), (0, 0), (20, 10)),
+            ("m".to_string(), (20, 0), (10, 10)),
+        ]
+    );
+
+    let list = render_list_with(app.view(), (0, 0), (100, 5), &font);
+    assert_eq!(
+        runs(&list),
+        vec![
+            ("mini".to_string(), (0, 0), (20, 5)),
+            ("max".to_string(), (20, 0), (22, 5)),
+        ]
+    );
+}
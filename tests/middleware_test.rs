@@ -0,0 +1,66 @@
+#[macro_use]
+extern crate pretty_assertions;
+#[macro_use]
+extern crate cinnabar;
+
+mod helper;
+
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+use self::helper::{reducer, Action, Message, Store};
+
+use cinnabar::middleware::{Logger, Next};
+use cinnabar::{App, Template};
+
+elements_for!(Store, Message, Action);
+
+#[derive(Clone)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn middleware_pipeline() {
+    let counter = Template::new(|store: &Store, _message: &Option<Message>| {
+        text(format!("{}", store.points)).done()
+    });
+
+    let log = SharedBuffer(Arc::new(Mutex::new(Vec::new())));
+
+    let mut app = App::new(Store { points: 0 }, counter, reducer)
+        .middleware(Logger::new(log.clone()))
+        // Reject increments past 2.
+        .middleware(
+            |store: Store, action: Action, next: Next<Store, Action>| match action {
+                Action::Increment if store.points >= 2 => store,
+                action => next(store, action),
+            },
+        )
+        // Assert the store only ever grows by one.
+        .middleware(|store: Store, action: Action, next: Next<Store, Action>| {
+            let before = store.points;
+            let after = next(store, action);
+            assert!(after.points == before || after.points == before + 1);
+            after
+        });
+
+    for _ in 0..4 {
+        app = app.action(Action::Increment);
+    }
+    app = app.action(Action::None);
+
+    assert_eq!(format!("{}", app.view()), "2");
+    assert_eq!(
+        String::from_utf8(log.0.lock().unwrap().clone()).unwrap(),
+        "Increment\nIncrement\nIncrement\nIncrement\nNone\n"
+    );
+}
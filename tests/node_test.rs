@@ -5,9 +5,15 @@ extern crate cinnabar;
 
 mod helper;
 
-use self::helper::{Action, Message, Store};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
-use cinnabar::Template;
+use self::helper::{reducer, Action, Message, Store};
+
+use cinnabar::vnode::StaticNode;
+#[allow(deprecated)]
+use cinnabar::Builder;
+use cinnabar::{App, ItemBuilder, Template, TemplateBuilder};
 
 elements_for!(Store, Message, Action);
 
@@ -75,3 +81,119 @@ fn dynamic_node() {
 "#
     );
 }
+
+#[test]
+fn static_subtrees_are_shared() {
+    let counter = Template::new(|store: &Store, _message: &Option<Message>| {
+        text(format!("{}", store.points)).done()
+    });
+
+    let dn = panel()
+        .child(panel().class("chrome").child(text("Header")))
+        .child(counter.clone())
+        .done();
+
+    let first = dn.render(&Store { points: 0 });
+    let second = dn.render(&Store { points: 1 });
+
+    let children = |node: &StaticNode<Action>| match node {
+        StaticNode::Container(container) => container.children().clone(),
+        _ => panic!("Expected a container"),
+    };
+
+    assert!(!first.ptr_eq(&second));
+    assert!(children(&first)[0].ptr_eq(&children(&second)[0]));
+    assert!(!children(&first)[1].ptr_eq(&children(&second)[1]));
+}
+
+#[test]
+fn memo_template() {
+    let renders = Arc::new(AtomicUsize::new(0));
+    let counted = renders.clone();
+
+    let counter = Template::memo(
+        |store: &Store, _message: &Option<Message>| store.points / 10,
+        move |store: &Store, _message: &Option<Message>| {
+            counted.fetch_add(1, Ordering::SeqCst);
+            text(format!("{}0+", store.points / 10)).done()
+        },
+    );
+
+    // Renders are remembered by the App.
+    let mut app = App::new(Store { points: 1 }, panel().child(counter.clone()), reducer);
+    let first = app.view().clone();
+    app.dispatch_all(vec![Action::Increment; 4]);
+    let second = app.view().clone();
+    app.dispatch_all(vec![Action::Increment; 7]);
+    let third = app.view().clone();
+
+    assert_eq!(renders.load(Ordering::SeqCst), 2);
+    assert_eq!(format!("{}", second), "<panel>00+</panel>");
+    assert_eq!(format!("{}", third), "<panel>10+</panel>");
+
+    let child = |node: &StaticNode<Action>| match node {
+        StaticNode::Container(container) => container.children()[0].clone(),
+        _ => panic!("Expected a container"),
+    };
+
+    assert!(child(&first).ptr_eq(&child(&second)));
+}
+
+#[test]
+fn memo_template_instances() {
+    let renders = Arc::new(AtomicUsize::new(0));
+    let counted = renders.clone();
+
+    let row = Template::memo(
+        |store: &Store, _message: &Option<Message>| store.points / 1000,
+        move |_store: &Store, _message: &Option<Message>| {
+            counted.fetch_add(1, Ordering::SeqCst);
+            text("row").done()
+        },
+    );
+
+    let mut table = panel();
+    for _ in 0..100 {
+        table = table.child(row.clone());
+    }
+
+    let app = App::new(Store { points: 0 }, table.done(), reducer);
+    assert_eq!(renders.load(Ordering::SeqCst), 100);
+
+    // Every row remembers its own render, so none of them renders again.
+    app.action(Action::Increment);
+    assert_eq!(renders.load(Ordering::SeqCst), 100);
+}
+
+#[test]
+fn builder_kinds() {
+    let counter = Template::new(|store: &Store, _message: &Option<Message>| {
+        text(format!("{}", store.points)).done()
+    });
+
+    let dn = panel()
+        .child(ItemBuilder::new("icon").id("logo").classes("small round"))
+        .child(TemplateBuilder::new(counter).key("counter"))
+        .child(text("points").wrap(false))
+        .done();
+
+    assert_eq!(
+        format!("{}", dn.render(&Store { points: 3 })),
+        r#"<panel><icon id="logo" class="round small">3points</panel>"#
+    );
+}
+
+#[test]
+#[allow(deprecated)]
+fn deprecated_builder() {
+    let node = Builder::<Store, Message, Action>::container("panel")
+        .child(Builder::text("OK"))
+        .child(Builder::item("icon").class("check"))
+        .done()
+        .render(&Store { points: 0 });
+
+    assert_eq!(
+        format!("{}", node),
+        "<panel>OK<icon class=\"check\"></panel>"
+    );
+}
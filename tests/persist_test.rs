@@ -0,0 +1,136 @@
+#![cfg(feature = "persist")]
+
+#[macro_use]
+extern crate pretty_assertions;
+#[macro_use]
+extern crate cinnabar;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+
+use std::env;
+use std::fs;
+
+use cinnabar::command::{Command, FakeExecutor};
+use cinnabar::persist::{self, PersistError};
+use cinnabar::{App, Template};
+
+#[derive(Serialize, Deserialize)]
+pub struct Store {
+    pub points: usize,
+    pub name: String,
+}
+
+pub enum Message {}
+
+pub enum Action {
+    Increment,
+    IncrementLater,
+}
+
+elements_for!(Store, Message, Action);
+
+fn counter() -> std::sync::Arc<Template<Store, Message, Action>> {
+    Template::new(|store: &Store, _message: &Option<Message>| {
+        panel()
+            .child(text(format!("{}: {} points", store.name, store.points)))
+            .done()
+    })
+}
+
+fn reducer(store: Store, action: Action) -> Store {
+    match action {
+        Action::Increment => Store {
+            points: store.points + 1,
+            ..store
+        },
+        Action::IncrementLater => store,
+    }
+}
+
+fn snapshot_path(name: &str) -> std::path::PathBuf {
+    env::temp_dir().join(format!("cinnabar-{}-{}.json", name, std::process::id()))
+}
+
+#[test]
+fn save_and_restore() {
+    let path = snapshot_path("store");
+
+    let app = App::new(
+        Store {
+            points: 0,
+            name: "Duck".to_string(),
+        },
+        counter(),
+        reducer,
+    );
+    let app = app.action(Action::Increment).action(Action::Increment);
+    app.save(&path, 1).unwrap();
+
+    let restored = App::restore(&path, 1, |_, store| Ok(store), counter(), reducer).unwrap();
+    assert_eq!(
+        format!("{}", restored.view()),
+        "<panel>Duck: 2 points</panel>"
+    );
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn restore_with_commands() {
+    let path = snapshot_path("commands");
+    persist::save(
+        &path,
+        1,
+        &Store {
+            points: 2,
+            name: "Duck".to_string(),
+        },
+    )
+    .unwrap();
+
+    let executor = FakeExecutor::new();
+    let app = App::restore_with_commands(
+        &path,
+        1,
+        |_, store| Ok(store),
+        counter(),
+        |store, action| match action {
+            Action::IncrementLater => (store, vec![Command::new(|| Action::Increment)]),
+            action => (reducer(store, action), Vec::new()),
+        },
+        executor.clone(),
+    )
+    .unwrap();
+    let app = app.action(Action::IncrementLater);
+
+    assert_eq!(executor.run_all(), 1);
+    assert_eq!(
+        format!("{}", app.poll().view()),
+        "<panel>Duck: 3 points</panel>"
+    );
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn migrate_older_snapshot() {
+    let path = snapshot_path("migrate");
+    fs::write(&path, r#"{"version":1,"store":{"points":3}}"#).unwrap();
+
+    let store: Store = persist::load(&path, 2, |version, mut store| {
+        assert_eq!(version, 1);
+        store["name"] = "Migrated".into();
+        Ok(store)
+    })
+    .unwrap();
+    assert_eq!(store.points, 3);
+    assert_eq!(store.name, "Migrated");
+
+    match persist::load::<Store, _, _>(&path, 0, |_, store| Ok(store)) {
+        Err(PersistError::UnsupportedVersion(version)) => assert_eq!(version, 1),
+        _ => panic!("Expected an unsupported version error"),
+    }
+
+    fs::remove_file(&path).unwrap();
+}
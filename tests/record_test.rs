@@ -0,0 +1,136 @@
+#[macro_use]
+extern crate pretty_assertions;
+#[macro_use]
+extern crate cinnabar;
+
+mod helper;
+
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use self::helper::{reducer, Action, Message, Store};
+
+use cinnabar::clock::ManualClock;
+use cinnabar::command::FakeExecutor;
+use cinnabar::record::{ActionLog, Recorder, ReplayError};
+use cinnabar::{App, Command, Template};
+
+elements_for!(Store, Message, Action);
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl FromStr for Action {
+    type Err = ();
+
+    fn from_str(action: &str) -> Result<Action, ()> {
+        match action {
+            "None" => Ok(Action::None),
+            "Increment" => Ok(Action::Increment),
+            _ => Err(()),
+        }
+    }
+}
+
+fn app() -> App<Store, Message, Action> {
+    let counter = Template::new(|store: &Store, _message: &Option<Message>| {
+        panel()
+            .child(text(format!("{} points", store.points)))
+            .done()
+    });
+
+    App::new(Store { points: 0 }, counter, reducer)
+}
+
+#[test]
+fn record_and_replay() {
+    let path = env::temp_dir().join(format!("cinnabar-actions-{}.log", std::process::id()));
+    let clock = ManualClock::new();
+
+    {
+        let recorder = Recorder::with_clock(std::fs::File::create(&path).unwrap(), clock.clone());
+        let mut app = app().middleware(recorder);
+
+        app = app.action(Action::Increment);
+        clock.advance(Duration::from_millis(250));
+        app = app.action(Action::None);
+        clock.advance(Duration::from_millis(1000));
+        app = app.action(Action::Increment);
+
+        assert_eq!(format!("{}", app.view()), "<panel>2 points</panel>");
+    }
+
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "0\tIncrement\n250\tNone\n1250\tIncrement\n"
+    );
+
+    let log = ActionLog::<Action>::load(&path).unwrap();
+    assert_eq!(log.entries()[2].0, Duration::from_millis(1250));
+
+    let replayed = app().replay(log);
+    assert_eq!(format!("{}", replayed.view()), "<panel>2 points</panel>");
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn replay_invalid_log() {
+    match ActionLog::<Action>::read("0\tIncrement\n10\tJump\n".as_bytes()) {
+        Err(ReplayError::Parse { line, .. }) => assert_eq!(line, 2),
+        _ => panic!("Expected a parse error"),
+    }
+}
+
+#[test]
+fn replay_with_commands() {
+    let app = |executor: FakeExecutor<Action>| {
+        let counter = Template::new(|store: &Store, _message: &Option<Message>| {
+            text(format!("{} points", store.points)).done()
+        });
+
+        // `None` asks for an increment through a command.
+        App::with_commands(
+            Store { points: 0 },
+            counter,
+            |store: Store, action| match action {
+                Action::Increment => (
+                    Store {
+                        points: store.points + 1,
+                    },
+                    Vec::new(),
+                ),
+                Action::None => (store, vec![Command::new(|| Action::Increment)]),
+            },
+            executor,
+        )
+    };
+
+    let path = env::temp_dir().join(format!("cinnabar-commands-{}.log", std::process::id()));
+    let executor = FakeExecutor::new();
+    {
+        let mut app = app(executor.clone()).middleware(Recorder::create(&path).unwrap());
+
+        app = app.action(Action::None);
+        executor.run_all();
+        app = app.poll();
+        assert_eq!(format!("{}", app.view()), "1 points");
+    }
+
+    let log = ActionLog::<Action>::load(&path).unwrap();
+    assert_eq!(log.entries().len(), 2);
+    std::fs::remove_file(&path).unwrap();
+
+    let executor = FakeExecutor::new();
+    let mut replayed = app(executor.clone()).replay(log);
+    assert_eq!(executor.pending(), 0);
+
+    executor.run_all();
+    replayed = replayed.poll();
+    assert_eq!(format!("{}", replayed.view()), "1 points");
+}
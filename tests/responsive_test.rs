@@ -0,0 +1,72 @@
+#[macro_use]
+extern crate pretty_assertions;
+#[macro_use]
+extern crate cinnabar;
+
+mod helper;
+
+use self::helper::{Action, Message, Store};
+
+use cinnabar::render::RenderCommand;
+use cinnabar::{render_list, App, Direction, Media, Override, Template};
+
+elements_for!(Store, Message, Action);
+
+fn positions(app: &mut App<Store, Message, Action>) -> Vec<(String, (u16, u16))> {
+    app.layout()
+        .iter()
+        .map(|command| match command {
+            RenderCommand::Text(text) => (text.node.content().to_string(), text.position),
+        })
+        .collect()
+}
+
+#[test]
+fn responsive_rules() {
+    let view = Template::new(|_store: &Store, _message: &Option<Message>| {
+        panel()
+            .direction(Direction::Row)
+            .when(
+                Media::new().max_width(79),
+                Override::Direction(Direction::Column),
+            )
+            .child(
+                panel()
+                    .when(Media::new().max_width(39), Override::Hidden)
+                    .child(text("Files")),
+            )
+            .child(text("Editor"))
+            .done()
+    });
+
+    let mut app = App::new(Store { points: 0 }, view, |store, _action| store);
+
+    app.resize((120, 40));
+    assert_eq!(
+        positions(&mut app),
+        vec![("Files".to_string(), (0, 0)), ("Editor".to_string(), (5, 0))]
+    );
+
+    app.resize((60, 40));
+    assert_eq!(
+        positions(&mut app),
+        vec![("Files".to_string(), (0, 0)), ("Editor".to_string(), (0, 1))]
+    );
+
+    app.resize((30, 40));
+    assert_eq!(positions(&mut app), vec![("Editor".to_string(), (0, 0))]);
+
+    // Rules follow the size the list is laid out in.
+    assert_eq!(render_list(app.view(), (0, 0), (100, 1)).len(), 2);
+}
+
+#[test]
+fn media_ranges() {
+    let media = Media::new().min_width(40).max_width(79).min_height(10);
+
+    assert!(media.matches((40, 10)));
+    assert!(media.matches((79, 50)));
+    assert!(!media.matches((80, 10)));
+    assert!(!media.matches((60, 9)));
+    assert!(Media::new().matches((0, 0)));
+}
@@ -0,0 +1,110 @@
+#[macro_use]
+extern crate pretty_assertions;
+#[macro_use]
+extern crate cinnabar;
+
+mod helper;
+
+use self::helper::{reducer, Action, Message, Store};
+
+use cinnabar::backend::{Event, FakeBackend};
+use cinnabar::render::RenderCommand;
+use cinnabar::vnode::DynamicNode;
+use cinnabar::{render_list, run, App, Overflow, Span, Template, TextStyle, Weight};
+
+elements_for!(Store, Message, Action);
+
+const KEYWORD: (u8, u8, u8, u8) = (200, 80, 40, 255);
+
+fn runs<T: Into<DynamicNode<Store, Message, Action>>>(
+    view: T,
+    size: (u16, u16),
+) -> Vec<(String, (u16, u16), TextStyle)> {
+    let node = view.into().render(&Store { points: 0 });
+
+    render_list(&node, (0, 0), size)
+        .iter()
+        .map(|command| match command {
+            RenderCommand::Text(text) => (text.content.to_string(), text.position, text.style()),
+        })
+        .collect()
+}
+
+fn snippet() -> Vec<Span<Action>> {
+    vec![
+        Span::new("fn ").color(KEYWORD).bold(),
+        Span::new("main() { "),
+        Span::new("return").color(KEYWORD).bold(),
+        Span::new(" }"),
+    ]
+}
+
+#[test]
+fn spans_wrap_as_one_paragraph() {
+    let keyword = TextStyle {
+        color: Some(KEYWORD),
+        weight: Weight::Bold,
+        italic: false,
+        underline: false,
+    };
+    let plain = TextStyle::default();
+
+    assert_eq!(
+        runs(rich(snippet()), (40, 1)),
+        vec![
+            ("fn ".to_string(), (0, 0), keyword),
+            ("main() { ".to_string(), (3, 0), plain),
+            ("return".to_string(), (12, 0), keyword),
+            (" }".to_string(), (18, 0), plain),
+        ]
+    );
+
+    assert_eq!(
+        runs(rich(snippet()), (10, 3)),
+        vec![
+            ("fn ".to_string(), (0, 0), keyword),
+            ("main()".to_string(), (3, 0), plain),
+            ("{ ".to_string(), (0, 1), plain),
+            ("return".to_string(), (2, 1), keyword),
+            (" }".to_string(), (8, 1), plain),
+        ]
+    );
+
+    // An ellipsis is styled like the span it follows.
+    let view = rich(snippet()).wrap(false).overflow(Overflow::Ellipsis);
+    assert_eq!(
+        runs(view, (16, 1)),
+        vec![
+            ("fn ".to_string(), (0, 0), keyword),
+            ("main() { ".to_string(), (3, 0), plain),
+            ("ret…".to_string(), (12, 0), keyword),
+        ]
+    );
+}
+
+#[test]
+fn click_span() {
+    let help = Template::new(|store: &Store, _message: &Option<Message>| {
+        panel()
+            .child(text(format!("{} ", store.points)))
+            .child(rich(vec![
+                Span::new("see "),
+                Span::new("more")
+                    .underline()
+                    .on_click(|_| Action::Increment),
+            ]))
+            .done()
+    });
+    let app = App::new(Store { points: 0 }, help, reducer);
+
+    let mut backend = FakeBackend::new((20, 1))
+        .event(Event::Click((3, 0)))
+        .idle()
+        .event(Event::Click((6, 0)))
+        .idle();
+
+    let app = run(app, &mut backend).unwrap();
+
+    assert_eq!(app.store().points, 1);
+    assert_eq!(backend.screen(), &["1 see more".to_string()]);
+}
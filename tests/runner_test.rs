@@ -0,0 +1,102 @@
+#[macro_use]
+extern crate pretty_assertions;
+#[macro_use]
+extern crate cinnabar;
+
+mod helper;
+
+use self::helper::{reducer, Action, Message, Store};
+
+use cinnabar::backend::{Event, FakeBackend};
+use cinnabar::measure::TextMeasurer;
+use cinnabar::render::Size;
+use cinnabar::{run, App, Runner, Template};
+
+elements_for!(Store, Message, Action);
+
+fn app() -> App<Store, Message, Action> {
+    let counter = Template::new(|store: &Store, _message: &Option<Message>| {
+        panel()
+            .child(text(format!("{} points ", store.points)))
+            .child(button().child(text("[+]").on_click(|_| Action::Increment)))
+            .done()
+    });
+
+    App::new(Store { points: 0 }, counter, reducer)
+}
+
+#[test]
+fn run_scripted_backend() {
+    let mut backend = FakeBackend::new((20, 2))
+        .event(Event::Click((0, 0)))
+        .idle()
+        .event(Event::Click((10, 0)))
+        .event(Event::Click((9, 0)))
+        .idle()
+        .event(Event::Resize((10, 1)))
+        .idle();
+
+    let app = run(app(), &mut backend).unwrap();
+
+    assert_eq!(app.store().points, 2);
+    assert_eq!(backend.screen(), &["2 points [".to_string()]);
+    // The initial frame, one for both clicks and one after resizing.
+    assert_eq!(backend.frames(), 3);
+}
+
+#[test]
+fn run_until_done() {
+    let mut backend = FakeBackend::new((20, 1))
+        .event(Event::Key('+'))
+        .event(Event::Key('x'))
+        .idle()
+        .event(Event::Key('+'))
+        .idle()
+        .event(Event::Key('+'))
+        .idle();
+
+    let app = Runner::new(app())
+        .on_key(|key| match key {
+            '+' => Some(Action::Increment),
+            _ => None,
+        })
+        .until(|store| store.points == 2)
+        .run(&mut backend)
+        .unwrap();
+
+    assert_eq!(app.store().points, 2);
+    assert_eq!(backend.screen(), &["2 points [+]".to_string()]);
+}
+
+// Every character is two cells wide.
+struct Wide;
+
+impl TextMeasurer for Wide {
+    fn measure(&self, text: &str) -> Size {
+        (2 * text.chars().count() as u16, 1)
+    }
+}
+
+#[test]
+fn run_container_handlers_and_measurer() {
+    let counter = Template::new(|store: &Store, _message: &Option<Message>| {
+        panel()
+            .child(text(format!("{} points ", store.points)))
+            .child(button().on_click(|_| Action::Increment).child(text("[+]")))
+            .done()
+    });
+
+    // The button is only at 20 when measured by `Wide`.
+    let mut backend = FakeBackend::new((40, 1))
+        .event(Event::Click((20, 0)))
+        .idle();
+
+    let mut app = run(
+        App::new(Store { points: 0 }, counter, reducer).measurer(Wide),
+        &mut backend,
+    )
+    .unwrap();
+
+    assert_eq!(app.store().points, 1);
+    assert_eq!(app.layout()[1].position(), (18, 0));
+}
@@ -0,0 +1,97 @@
+#[macro_use]
+extern crate pretty_assertions;
+#[macro_use]
+extern crate cinnabar;
+
+mod helper;
+
+use std::sync::mpsc::channel;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use self::helper::{reducer, Action, Message, Store};
+
+use cinnabar::clock::ManualClock;
+use cinnabar::{App, Subscription, Template};
+
+elements_for!(Store, Message, Action);
+
+fn counter() -> Arc<Template<Store, Message, Action>> {
+    Template::new(|store: &Store, _message: &Option<Message>| {
+        text(format!("{}", store.points)).done()
+    })
+}
+
+#[test]
+fn interval_subscription() {
+    let clock = ManualClock::new();
+
+    let mut app = App::new(Store { points: 0 }, counter(), reducer)
+        .clock(clock.clone())
+        .subscriptions(|store: &Store| {
+            if store.points < 2 {
+                vec![Subscription::interval(
+                    "tick",
+                    Duration::from_secs(1),
+                    || Action::Increment,
+                )]
+            } else {
+                Vec::new()
+            }
+        });
+
+    app = app.poll();
+    assert_eq!(format!("{}", app.view()), "0");
+
+    clock.advance(Duration::from_millis(1500));
+    app = app.poll();
+    assert_eq!(format!("{}", app.view()), "1");
+
+    clock.advance(Duration::from_secs(5));
+    app = app.poll();
+    assert_eq!(format!("{}", app.view()), "2");
+
+    clock.advance(Duration::from_secs(5));
+    app = app.poll();
+    assert_eq!(format!("{}", app.view()), "2");
+}
+
+#[test]
+fn zero_interval_subscription() {
+    let clock = ManualClock::new();
+
+    let mut app = App::new(Store { points: 0 }, counter(), reducer)
+        .clock(clock.clone())
+        .subscriptions(|_store: &Store| {
+            vec![Subscription::interval(
+                "tick",
+                Duration::from_secs(0),
+                || Action::Increment,
+            )]
+        });
+
+    app = app.poll();
+    app = app.poll();
+    assert_eq!(format!("{}", app.view()), "2");
+
+    clock.advance(Duration::from_secs(5));
+    app = app.poll();
+    assert_eq!(format!("{}", app.view()), "3");
+}
+
+#[test]
+fn channel_subscription() {
+    let (sender, receiver) = channel();
+    let receiver = Arc::new(Mutex::new(receiver));
+
+    let mut app =
+        App::new(Store { points: 0 }, counter(), reducer).subscriptions(move |_store: &Store| {
+            vec![Subscription::channel("events", receiver.clone())]
+        });
+
+    sender.send(Action::Increment).unwrap();
+    sender.send(Action::Increment).unwrap();
+    app = app.poll();
+
+    assert_eq!(format!("{}", app.view()), "2");
+}
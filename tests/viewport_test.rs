@@ -0,0 +1,73 @@
+#[macro_use]
+extern crate pretty_assertions;
+#[macro_use]
+extern crate cinnabar;
+
+mod helper;
+
+use self::helper::{reducer, Action, Message, Store};
+
+use cinnabar::render::RenderCommand;
+use cinnabar::{App, Template};
+
+elements_for!(Store, Message, Action);
+
+fn app() -> App<Store, Message, Action> {
+    let status = Template::sized(|store: &Store, _message: &Option<Message>, (width, _)| {
+        if width < 20 {
+            text(format!("{}p", store.points)).done()
+        } else {
+            text(format!("{} points", store.points)).done()
+        }
+    });
+
+    let view = Template::memo(
+        |store: &Store, _message: &Option<Message>| store.points,
+        move |_store: &Store, _message: &Option<Message>| {
+            panel().child(status.clone()).done()
+        },
+    );
+
+    App::new(Store { points: 3 }, view, reducer)
+}
+
+fn texts(app: &mut App<Store, Message, Action>) -> Vec<(String, (u16, u16))> {
+    app.layout()
+        .iter()
+        .map(|command| match command {
+            RenderCommand::Text(text) => (text.node.content().to_string(), text.size),
+        })
+        .collect()
+}
+
+#[test]
+fn resize_renders_for_viewport() {
+    let mut app = app();
+    assert_eq!(app.viewport(), (80, 24));
+    assert_eq!(format!("{}", app.view()), "<panel>3 points</panel>");
+
+    app.resize((10, 5));
+    assert_eq!(app.viewport(), (10, 5));
+    assert_eq!(format!("{}", app.view()), "<panel>3p</panel>");
+    assert_eq!(texts(&mut app), vec![("3p".to_string(), (2, 1))]);
+
+    app.resize((6, 1));
+    app.dispatch(Action::Increment);
+    app.resize((30, 1));
+    assert_eq!(texts(&mut app), vec![("4 points".to_string(), (8, 1))]);
+}
+
+#[test]
+fn layout_is_cached() {
+    let mut app = app();
+
+    let first = app.layout() as *const _;
+    assert_eq!(app.layout() as *const _, first);
+
+    // The memoized view stays the same, so nothing has to be laid out again.
+    app.dispatch(Action::None);
+    assert_eq!(app.layout() as *const _, first);
+
+    app.dispatch(Action::Increment);
+    assert_eq!(texts(&mut app), vec![("4 points".to_string(), (8, 1))]);
+}
@@ -0,0 +1,123 @@
+#[macro_use]
+extern crate pretty_assertions;
+#[macro_use]
+extern crate cinnabar;
+
+mod helper;
+
+use self::helper::{Action, Message, Store};
+
+use cinnabar::render::RenderCommand;
+use cinnabar::vnode::DynamicNode;
+use cinnabar::{render_list, Direction, Overflow};
+
+elements_for!(Store, Message, Action);
+
+fn runs<T: Into<DynamicNode<Store, Message, Action>>>(
+    view: T,
+    size: (u16, u16),
+) -> Vec<(String, (u16, u16))> {
+    let node = view.into().render(&Store { points: 0 });
+
+    render_list(&node, (0, 0), size)
+        .iter()
+        .map(|command| match command {
+            RenderCommand::Text(text) => (text.content.to_string(), text.position),
+        })
+        .collect()
+}
+
+fn run(content: &str, x: u16, y: u16) -> (String, (u16, u16)) {
+    (content.to_string(), (x, y))
+}
+
+#[test]
+fn wrap_at_break_opportunities() {
+    let view = panel()
+        .child(text("> "))
+        .child(text("Long descriptions run off the screen"));
+
+    assert_eq!(
+        runs(view, (16, 10)),
+        vec![
+            run("> ", 0, 0),
+            run("Long", 2, 0),
+            run("descriptions", 2, 1),
+            run("run off the", 2, 2),
+            run("screen", 2, 3),
+        ]
+    );
+}
+
+#[test]
+fn newlines_and_long_words() {
+    let view = text("first\n\nunbreakable-word\r\nこんにちは世界");
+
+    assert_eq!(
+        runs(view, (8, 10)),
+        vec![
+            run("first", 0, 0),
+            run("unbreaka", 0, 2),
+            run("ble-word", 0, 3),
+            run("こんにち", 0, 4),
+            run("は世界", 0, 5),
+        ]
+    );
+}
+
+#[test]
+fn without_wrapping() {
+    let view = text("no wrapping here\nsecond line").wrap(false);
+    assert_eq!(
+        runs(view, (10, 10)),
+        vec![run("no wrappin", 0, 0), run("second lin", 0, 1)]
+    );
+
+    // A trailing newline does not add an empty line either way.
+    for &wrap in &[true, false] {
+        let view = panel()
+            .direction(Direction::Column)
+            .child(text("abc\n").wrap(wrap))
+            .child(text("next"));
+        assert_eq!(
+            runs(view, (10, 10)),
+            vec![run("abc", 0, 0), run("next", 0, 1)]
+        );
+    }
+
+    // Lines below the parent's bottom are left out.
+    let view = text("one two three");
+    assert_eq!(runs(view, (5, 2)), vec![run("one", 0, 0), run("two", 0, 1)]);
+}
+
+#[test]
+fn overflow_modes() {
+    let path = "/home/user/projects/cinnabar/src/app.rs";
+    let cut = |overflow| {
+        let view = text(path).wrap(false).overflow(overflow);
+        runs(view, (16, 1))
+            .into_iter()
+            .map(|(content, _)| content)
+            .collect::<String>()
+    };
+
+    assert_eq!(cut(Overflow::Clip), "/home/user/proje");
+    assert_eq!(cut(Overflow::Ellipsis), "/home/user/proj…");
+    assert_eq!(cut(Overflow::MiddleEllipsis), "/home/us…/app.rs");
+    assert_eq!(cut(Overflow::Scroll(6)), "user/projects/ci");
+    assert_eq!(cut(Overflow::Scroll(60)), "");
+
+    // Wrapping text is never cut, so it does not scroll either.
+    let view = text("one two three").overflow(Overflow::Scroll(2));
+    assert_eq!(
+        runs(view, (5, 3)),
+        vec![run("one", 0, 0), run("two", 0, 1), run("three", 0, 2)]
+    );
+
+    let view = text("short.rs").wrap(false).overflow(Overflow::MiddleEllipsis);
+    assert_eq!(runs(view, (16, 1)), vec![run("short.rs", 0, 0)]);
+
+    // Wide characters are never split to make room for the ellipsis.
+    let view = text("日本語のファイル名.txt").wrap(false).overflow(Overflow::MiddleEllipsis);
+    assert_eq!(runs(view, (10, 1)), vec![run("日本….txt", 0, 0)]);
+}